@@ -0,0 +1,96 @@
+//! Zero-copy views of image data as [ndarray] arrays
+
+use ndarray::{ArrayView2, ArrayView3, ShapeBuilder};
+
+use crate::{
+    pixel_format::{Mono8, RGB8},
+    ImageStride,
+};
+
+/// A `Mono8` image which can be viewed as an [ndarray::ArrayView2].
+pub trait AsArrayViewMono8: ImageStride<Mono8> {
+    /// View the image as an array of shape `[height, width]`.
+    ///
+    /// This does not copy the data. Row padding is skipped using the stride.
+    fn as_array_view_mono8(&self) -> ArrayView2<'_, u8>;
+}
+
+impl<S: ImageStride<Mono8> + ?Sized> AsArrayViewMono8 for S {
+    fn as_array_view_mono8(&self) -> ArrayView2<'_, u8> {
+        let shape = (self.height() as usize, self.width() as usize);
+        let strides = (self.stride(), 1);
+        ArrayView2::from_shape(shape.strides(strides), self.image_data())
+            .expect("image buffer too small for image dimensions")
+    }
+}
+
+/// An `RGB8` image which can be viewed as an [ndarray::ArrayView3].
+pub trait AsArrayViewRGB8: ImageStride<RGB8> {
+    /// View the image as an array of shape `[height, width, 3]`.
+    ///
+    /// This does not copy the data. Row padding is skipped using the stride.
+    fn as_array_view_rgb8(&self) -> ArrayView3<'_, u8>;
+}
+
+impl<S: ImageStride<RGB8> + ?Sized> AsArrayViewRGB8 for S {
+    fn as_array_view_rgb8(&self) -> ArrayView3<'_, u8> {
+        let shape = (self.height() as usize, self.width() as usize, 3);
+        let strides = (self.stride(), 3, 1);
+        ArrayView3::from_shape(shape.strides(strides), self.image_data())
+            .expect("image buffer too small for image dimensions")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AsArrayViewMono8, AsArrayViewRGB8};
+    use crate::{
+        image_ref::ImageRef,
+        pixel_format::{Mono8, RGB8},
+    };
+
+    #[test]
+    fn test_sum_mono8() {
+        const STRIDE: usize = 7;
+        let (width, height) = (5, 3);
+        let mut buf = vec![0u8; STRIDE * height];
+        let mut expected = 0u64;
+        for row in 0..height {
+            for col in 0..STRIDE {
+                let value = (row * 10 + col) as u8;
+                buf[row * STRIDE + col] = value;
+                if col < width {
+                    expected += value as u64;
+                }
+            }
+        }
+        let im = ImageRef::<Mono8>::new(width as u32, height as u32, STRIDE, &buf).unwrap();
+        let view = im.as_array_view_mono8();
+        assert_eq!(view.shape(), &[height, width]);
+        let actual: u64 = view.iter().map(|x| *x as u64).sum();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_sum_rgb8() {
+        const STRIDE: usize = 8;
+        let (width, height) = (2, 2);
+        let mut buf = vec![0u8; STRIDE * height];
+        let mut expected = 0u64;
+        for row in 0..height {
+            for col in 0..STRIDE {
+                let value = (row * 10 + col) as u8;
+                buf[row * STRIDE + col] = value;
+                if col < width * 3 {
+                    expected += value as u64;
+                }
+            }
+        }
+        let im = ImageRef::<RGB8>::new(width as u32, height as u32, STRIDE, &buf).unwrap();
+        let view = im.as_array_view_rgb8();
+        assert_eq!(view.shape(), &[height, width, 3]);
+        assert_eq!(view[[1, 1, 2]], 15);
+        let actual: u64 = view.iter().map(|x| *x as u64).sum();
+        assert_eq!(actual, expected);
+    }
+}
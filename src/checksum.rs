@@ -0,0 +1,46 @@
+//! Checksums of image data using [crc32fast]
+
+use crate::{iter::HasRowChunksExact, ImageStride, PixelFormat};
+
+/// An image whose valid pixels can be checksummed.
+pub trait Crc32Valid<F>: ImageStride<F> {
+    /// Compute the CRC32 checksum of the valid pixels in each row.
+    ///
+    /// Row padding is not included, so images with identical pixels have the
+    /// same checksum regardless of their stride.
+    fn crc32_valid(&self) -> u32;
+}
+
+impl<S, F> Crc32Valid<F> for S
+where
+    S: ImageStride<F> + ?Sized,
+    F: PixelFormat,
+{
+    fn crc32_valid(&self) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        for row in self.rowchunks_exact() {
+            hasher.update(row);
+        }
+        hasher.finalize()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Crc32Valid;
+    use crate::{image_ref::ImageRef, pixel_format::Mono8};
+
+    #[test]
+    fn test_crc_ignores_padding() {
+        let packed_buf = [1, 2, 3, 4, 5, 6];
+        let padded_buf = [1, 2, 3, 0xaa, 4, 5, 6, 0xbb];
+        let packed = ImageRef::<Mono8>::new(3, 2, 3, &packed_buf).unwrap();
+        let padded = ImageRef::<Mono8>::new(3, 2, 4, &padded_buf).unwrap();
+        assert_eq!(packed.crc32_valid(), padded.crc32_valid());
+        assert_eq!(packed.crc32_valid(), crc32fast::hash(&packed_buf));
+
+        let other_buf = [1, 2, 3, 4, 5, 7];
+        let other = ImageRef::<Mono8>::new(3, 2, 3, &other_buf).unwrap();
+        assert_ne!(packed.crc32_valid(), other.crc32_valid());
+    }
+}
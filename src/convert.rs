@@ -0,0 +1,563 @@
+//! Conversions between pixel formats
+//!
+//! The functions in this module copy the source image, respecting its stride,
+//! into a new, tightly packed [OImage].
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{
+    error::DimMismatch,
+    iter::HasRowChunksExact,
+    owned::OImage,
+    pixel_format::{Mono16, Mono16Be, Mono16Le, Mono8, RGB8, RGBA8},
+    ImageStride, PixelFormat,
+};
+
+/// Swap the byte order of each 2-byte sample.
+fn swap_bytes_16<SRC, DST>(src: &dyn ImageStride<SRC>) -> OImage<DST>
+where
+    SRC: PixelFormat,
+    DST: PixelFormat,
+{
+    let width = src.width();
+    let height = src.height();
+    let stride = width as usize * 2;
+    let mut buf = Vec::with_capacity(stride * height as usize);
+    for row in src.rowchunks_exact() {
+        for sample in row.chunks_exact(2) {
+            buf.push(sample[1]);
+            buf.push(sample[0]);
+        }
+    }
+    OImage::from_parts(width, height, stride, buf)
+}
+
+/// Convert big-endian 16-bit luminance to little-endian.
+pub fn swap_endianness_be_to_le(src: &dyn ImageStride<Mono16Be>) -> OImage<Mono16Le> {
+    swap_bytes_16(src)
+}
+
+/// Convert little-endian 16-bit luminance to big-endian.
+pub fn swap_endianness_le_to_be(src: &dyn ImageStride<Mono16Le>) -> OImage<Mono16Be> {
+    swap_bytes_16(src)
+}
+
+/// Convert grayscale to color by copying each value to all three channels.
+pub fn mono8_to_rgb8(src: &dyn ImageStride<Mono8>) -> OImage<RGB8> {
+    let width = src.width();
+    let height = src.height();
+    let stride = width as usize * 3;
+    let mut buf = Vec::with_capacity(stride * height as usize);
+    for row in src.rowchunks_exact() {
+        for gray in row {
+            buf.extend_from_slice(&[*gray, *gray, *gray]);
+        }
+    }
+    OImage::from_parts(width, height, stride, buf)
+}
+
+/// The Rec. 601 luma of an RGB pixel using 8-bit fixed point weights.
+#[inline]
+fn luma_601(r: u8, g: u8, b: u8) -> u8 {
+    ((77 * r as u16 + 150 * g as u16 + 29 * b as u16 + 128) >> 8) as u8
+}
+
+/// Convert one row of RGB pixels to luma, appending to `out`.
+#[cfg(not(feature = "simd"))]
+fn rgb8_row_to_mono8(row: &[u8], out: &mut Vec<u8>) {
+    out.extend(row.chunks_exact(3).map(|px| luma_601(px[0], px[1], px[2])));
+}
+
+/// Convert one row of RGB pixels to luma, appending to `out`.
+///
+/// Eight pixels are processed per iteration. The 16-bit arithmetic is
+/// identical to [luma_601], which handles the remaining pixels.
+#[cfg(feature = "simd")]
+fn rgb8_row_to_mono8(row: &[u8], out: &mut Vec<u8>) {
+    use wide::u16x8;
+    let chunks = row.chunks_exact(3 * 8);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        let mut r = [0u16; 8];
+        let mut g = [0u16; 8];
+        let mut b = [0u16; 8];
+        for (i, px) in chunk.chunks_exact(3).enumerate() {
+            r[i] = px[0] as u16;
+            g[i] = px[1] as u16;
+            b[i] = px[2] as u16;
+        }
+        let y: u16x8 = (u16x8::from(r) * u16x8::splat(77)
+            + u16x8::from(g) * u16x8::splat(150)
+            + u16x8::from(b) * u16x8::splat(29)
+            + u16x8::splat(128))
+            >> 8_u32;
+        out.extend(y.to_array().iter().map(|v| *v as u8));
+    }
+    out.extend(
+        remainder
+            .chunks_exact(3)
+            .map(|px| luma_601(px[0], px[1], px[2])),
+    );
+}
+
+/// Convert color to grayscale using the Rec. 601 luma weights.
+///
+/// Each output value is `(77*r + 150*g + 29*b + 128) >> 8`. With the `simd`
+/// feature, multiple pixels are converted per iteration with bit-identical
+/// results.
+pub fn rgb8_to_mono8(src: &dyn ImageStride<RGB8>) -> OImage<Mono8> {
+    let width = src.width();
+    let height = src.height();
+    let stride = width as usize;
+    let mut buf = Vec::with_capacity(stride * height as usize);
+    for row in src.rowchunks_exact() {
+        rgb8_row_to_mono8(row, &mut buf);
+    }
+    OImage::from_parts(width, height, stride, buf)
+}
+
+/// Convert RGBA to RGB by dropping the alpha channel.
+pub fn rgba8_to_rgb8(src: &dyn ImageStride<RGBA8>) -> OImage<RGB8> {
+    let width = src.width();
+    let height = src.height();
+    let stride = width as usize * 3;
+    let mut buf = Vec::with_capacity(stride * height as usize);
+    for row in src.rowchunks_exact() {
+        for px in row.chunks_exact(4) {
+            buf.extend_from_slice(&px[..3]);
+        }
+    }
+    OImage::from_parts(width, height, stride, buf)
+}
+
+/// Convert RGB to RGBA, setting the alpha channel of every pixel to `alpha`.
+pub fn rgb8_to_rgba8(src: &dyn ImageStride<RGB8>, alpha: u8) -> OImage<RGBA8> {
+    let width = src.width();
+    let height = src.height();
+    let stride = width as usize * 4;
+    let mut buf = Vec::with_capacity(stride * height as usize);
+    for row in src.rowchunks_exact() {
+        for px in row.chunks_exact(3) {
+            buf.extend_from_slice(&[px[0], px[1], px[2], alpha]);
+        }
+    }
+    OImage::from_parts(width, height, stride, buf)
+}
+
+/// Split a color image into red, green and blue planes.
+pub fn split_channels_rgb8(src: &dyn ImageStride<RGB8>) -> [OImage<Mono8>; 3] {
+    let width = src.width();
+    let height = src.height();
+    let stride = width as usize;
+    let mut planes = [
+        Vec::with_capacity(stride * height as usize),
+        Vec::with_capacity(stride * height as usize),
+        Vec::with_capacity(stride * height as usize),
+    ];
+    for row in src.rowchunks_exact() {
+        for px in row.chunks_exact(3) {
+            for (plane, value) in planes.iter_mut().zip(px.iter()) {
+                plane.push(*value);
+            }
+        }
+    }
+    let [r, g, b] = planes;
+    [
+        OImage::from_parts(width, height, stride, r),
+        OImage::from_parts(width, height, stride, g),
+        OImage::from_parts(width, height, stride, b),
+    ]
+}
+
+/// Merge red, green and blue planes into a color image.
+///
+/// The planes must have the same dimensions.
+pub fn merge_channels(
+    r: &dyn ImageStride<Mono8>,
+    g: &dyn ImageStride<Mono8>,
+    b: &dyn ImageStride<Mono8>,
+) -> Result<OImage<RGB8>, DimMismatch> {
+    let width = r.width();
+    let height = r.height();
+    for plane in [g, b].iter() {
+        if plane.width() != width || plane.height() != height {
+            return Err(DimMismatch);
+        }
+    }
+    let stride = width as usize * 3;
+    let mut buf = Vec::with_capacity(stride * height as usize);
+    for ((r_row, g_row), b_row) in r
+        .rowchunks_exact()
+        .zip(g.rowchunks_exact())
+        .zip(b.rowchunks_exact())
+    {
+        for ((r, g), b) in r_row.iter().zip(g_row.iter()).zip(b_row.iter()) {
+            buf.extend_from_slice(&[*r, *g, *b]);
+        }
+    }
+    Ok(OImage::from_parts(width, height, stride, buf))
+}
+
+/// Scale 16-bit luminance with `active_bits` significant bits to 8 bits.
+///
+/// Each value is shifted right by `active_bits - 8`, so e.g. 12-bit data in
+/// a 16-bit container maps 4095 to 255. Values with more than `active_bits`
+/// significant bits saturate at 255. `active_bits` is clamped to the range 8
+/// to 16.
+pub fn normalize_to_mono8(src: &dyn ImageStride<Mono16>, active_bits: u8) -> OImage<Mono8> {
+    let shift = active_bits.clamp(8, 16) - 8;
+    let width = src.width();
+    let height = src.height();
+    let stride = width as usize;
+    let mut buf = Vec::with_capacity(stride * height as usize);
+    for row in src.rowchunks_exact() {
+        for sample in row.chunks_exact(2) {
+            let value = u16::from_le_bytes([sample[0], sample[1]]) >> shift;
+            buf.push(value.min(255) as u8);
+        }
+    }
+    OImage::from_parts(width, height, stride, buf)
+}
+
+// The conversions between owned images below cannot fail, so they are
+// implemented with `From`. `TryFrom` and `try_into()` are then available
+// through the blanket implementations in `std`.
+
+/// Convert to grayscale using [rgb8_to_mono8].
+impl From<OImage<RGB8>> for OImage<Mono8> {
+    fn from(orig: OImage<RGB8>) -> Self {
+        rgb8_to_mono8(&orig)
+    }
+}
+
+/// Convert to color using [mono8_to_rgb8].
+impl From<OImage<Mono8>> for OImage<RGB8> {
+    fn from(orig: OImage<Mono8>) -> Self {
+        mono8_to_rgb8(&orig)
+    }
+}
+
+/// Add an opaque alpha channel using [rgb8_to_rgba8].
+impl From<OImage<RGB8>> for OImage<RGBA8> {
+    fn from(orig: OImage<RGB8>) -> Self {
+        rgb8_to_rgba8(&orig, 255)
+    }
+}
+
+/// Drop the alpha channel using [rgba8_to_rgb8].
+impl From<OImage<RGBA8>> for OImage<RGB8> {
+    fn from(orig: OImage<RGBA8>) -> Self {
+        rgba8_to_rgb8(&orig)
+    }
+}
+
+/// Swap the byte order using [swap_endianness_be_to_le].
+impl From<OImage<Mono16Be>> for OImage<Mono16Le> {
+    fn from(orig: OImage<Mono16Be>) -> Self {
+        swap_endianness_be_to_le(&orig)
+    }
+}
+
+/// Swap the byte order using [swap_endianness_le_to_be].
+impl From<OImage<Mono16Le>> for OImage<Mono16Be> {
+    fn from(orig: OImage<Mono16Le>) -> Self {
+        swap_endianness_le_to_be(&orig)
+    }
+}
+
+/// The color filter arrangement of a Bayer mosaic.
+///
+/// The name lists the colors of the top-left 2x2 cell in raster order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BayerOrder {
+    RGGB,
+    BGGR,
+    GRBG,
+    GBRG,
+}
+
+impl BayerOrder {
+    /// The channel (0 = red, 1 = green, 2 = blue) sampled at `(x, y)`.
+    #[inline]
+    fn channel_at(self, x: usize, y: usize) -> usize {
+        let cell = match self {
+            BayerOrder::RGGB => [0, 1, 1, 2],
+            BayerOrder::BGGR => [2, 1, 1, 0],
+            BayerOrder::GRBG => [1, 0, 2, 1],
+            BayerOrder::GBRG => [1, 2, 0, 1],
+        };
+        cell[(y % 2) * 2 + x % 2]
+    }
+}
+
+/// The interpolation used to reconstruct color from a Bayer mosaic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DemosaicMethod {
+    /// Each channel is copied from the nearest sample in the same 2x2 cell.
+    ///
+    /// Missing green at a red or blue pixel is taken from the same row.
+    Nearest,
+    /// Each missing channel is the average of the neighboring samples of that
+    /// channel in the surrounding 3x3 neighborhood.
+    Bilinear,
+}
+
+/// Reconstruct a color image from raw Bayer mosaic data.
+///
+/// The source is treated as raw mosaic samples arranged according to
+/// `order`. Pixels at the image border use only the neighbors inside the
+/// image.
+pub fn debayer(
+    src: &dyn ImageStride<Mono8>,
+    order: BayerOrder,
+    method: DemosaicMethod,
+) -> OImage<RGB8> {
+    let width = src.width() as usize;
+    let height = src.height() as usize;
+    let rows: Vec<&[u8]> = src.rowchunks_exact().collect();
+    let stride = width * 3;
+    let mut buf = Vec::with_capacity(stride * height);
+    for y in 0..height {
+        for x in 0..width {
+            let own = order.channel_at(x, y);
+            let mut rgb = [0u8; 3];
+            match method {
+                DemosaicMethod::Nearest => {
+                    let cx = x & !1;
+                    let cy = y & !1;
+                    let right = (cx + 1).min(width - 1);
+                    let below = (cy + 1).min(height - 1);
+                    for &(sx, sy) in [(cx, cy), (right, cy), (cx, below), (right, below)].iter() {
+                        rgb[order.channel_at(sx, sy)] = rows[sy][sx];
+                    }
+                    if own == 1 {
+                        rgb[1] = rows[y][x];
+                    } else {
+                        let sx = if x == cx { right } else { cx };
+                        if order.channel_at(sx, y) == 1 {
+                            rgb[1] = rows[y][sx];
+                        }
+                    }
+                    rgb[own] = rows[y][x];
+                }
+                DemosaicMethod::Bilinear => {
+                    let (x0, x1) = (x.saturating_sub(1), (x + 2).min(width));
+                    let (y0, y1) = (y.saturating_sub(1), (y + 2).min(height));
+                    let mut sums = [0u32; 3];
+                    let mut counts = [0u32; 3];
+                    for (sy, row) in (y0..y1).zip(rows[y0..y1].iter()) {
+                        for (sx, value) in (x0..x1).zip(row[x0..x1].iter()) {
+                            let channel = order.channel_at(sx, sy);
+                            sums[channel] += *value as u32;
+                            counts[channel] += 1;
+                        }
+                    }
+                    for ((out, sum), count) in rgb.iter_mut().zip(sums.iter()).zip(counts.iter()) {
+                        if let Some(mean) = (sum + count / 2).checked_div(*count) {
+                            *out = mean as u8;
+                        }
+                    }
+                    rgb[own] = rows[y][x];
+                }
+            }
+            buf.extend_from_slice(&rgb);
+        }
+    }
+    OImage::from_parts(src.width(), src.height(), stride, buf)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{image_ref::ImageRef, ImageData, Stride};
+
+    #[test]
+    fn test_swap_endianness() {
+        // Two rows of two pixels with two bytes of padding per row.
+        let buf = [0x01, 0x02, 0x03, 0x04, 0xff, 0xff, 0x05, 0x06, 0x07, 0x08];
+        let be = ImageRef::<Mono16Be>::new(2, 2, 6, &buf).unwrap();
+
+        let le = swap_endianness_be_to_le(&be);
+        assert_eq!(le.width(), 2);
+        assert_eq!(le.height(), 2);
+        assert_eq!(le.stride(), 4);
+        assert_eq!(
+            le.image_data(),
+            &[0x02, 0x01, 0x04, 0x03, 0x06, 0x05, 0x08, 0x07]
+        );
+
+        let be2 = swap_endianness_le_to_be(&le);
+        assert_eq!(be2.image_data(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_mono8_to_rgb8() {
+        let buf = [0, 17, 0xff, 1, 255, 128];
+        let mono = ImageRef::<Mono8>::new(2, 2, 4, &buf).unwrap();
+        let rgb = mono8_to_rgb8(&mono);
+        assert_eq!((rgb.width(), rgb.height(), rgb.stride()), (2, 2, 6));
+        assert_eq!(
+            rgb.image_data(),
+            &[0, 0, 0, 17, 17, 17, 255, 255, 255, 128, 128, 128]
+        );
+    }
+
+    #[test]
+    fn test_rgb8_to_mono8() {
+        // A pseudo-random image whose width is not a multiple of the SIMD
+        // width, with row padding.
+        let (width, height, stride) = (37, 5, 37 * 3 + 2);
+        let mut state = 12345u32;
+        let buf: Vec<u8> = (0..stride * height)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                (state >> 16) as u8
+            })
+            .collect();
+        let rgb = ImageRef::<RGB8>::new(width, height as u32, stride, &buf).unwrap();
+        let mono = rgb8_to_mono8(&rgb);
+        assert_eq!((mono.width(), mono.height(), mono.stride()), (37, 5, 37));
+        let expected: Vec<u8> = rgb
+            .rowchunks_exact()
+            .flat_map(|row| row.chunks_exact(3).map(|px| luma_601(px[0], px[1], px[2])))
+            .collect();
+        assert_eq!(mono.image_data(), &expected[..]);
+
+        assert_eq!(luma_601(255, 255, 255), 255);
+        assert_eq!(luma_601(0, 0, 0), 0);
+        assert_eq!(luma_601(255, 0, 0), 77);
+    }
+
+    #[test]
+    fn test_split_merge_channels() {
+        let buf = [1, 2, 3, 4, 5, 6, 0, 7, 8, 9, 10, 11, 12];
+        let rgb = ImageRef::<RGB8>::new(2, 2, 7, &buf).unwrap();
+        let [r, g, b] = split_channels_rgb8(&rgb);
+        assert_eq!(r.image_data(), &[1, 4, 7, 10]);
+        assert_eq!(g.image_data(), &[2, 5, 8, 11]);
+        assert_eq!(b.image_data(), &[3, 6, 9, 12]);
+
+        let merged = merge_channels(&r, &g, &b).unwrap();
+        assert_eq!(
+            (merged.width(), merged.height(), merged.stride()),
+            (2, 2, 6)
+        );
+        assert_eq!(
+            merged.image_data(),
+            &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]
+        );
+
+        let other_buf = [0u8; 4];
+        let other = ImageRef::<Mono8>::new(4, 1, 4, &other_buf).unwrap();
+        assert_eq!(merge_channels(&r, &g, &other).unwrap_err(), DimMismatch);
+    }
+
+    #[test]
+    fn test_rgb8_rgba8() {
+        let buf = [1, 2, 3, 4, 5, 6, 0, 7, 8, 9, 10, 11, 12];
+        let rgb = ImageRef::<RGB8>::new(2, 2, 7, &buf).unwrap();
+        let rgba = rgb8_to_rgba8(&rgb, 128);
+        assert_eq!((rgba.width(), rgba.height(), rgba.stride()), (2, 2, 8));
+        assert_eq!(
+            rgba.image_data(),
+            &[1, 2, 3, 128, 4, 5, 6, 128, 7, 8, 9, 128, 10, 11, 12, 128]
+        );
+
+        let rgb2 = rgba8_to_rgb8(&rgba);
+        assert_eq!((rgb2.width(), rgb2.height(), rgb2.stride()), (2, 2, 6));
+        assert_eq!(rgb2.image_data(), &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+    }
+
+    #[test]
+    fn test_normalize_to_mono8() {
+        let values: [u16; 4] = [0, 0x0fff, 0x0800, 0x1000];
+        let buf: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let im = ImageRef::<Mono16>::new(4, 1, 8, &buf).unwrap();
+        let mono = normalize_to_mono8(&im, 12);
+        assert_eq!((mono.width(), mono.height(), mono.stride()), (4, 1, 4));
+        // The last value exceeds 12 bits and saturates.
+        assert_eq!(mono.image_data(), &[0, 255, 128, 255]);
+
+        let full = normalize_to_mono8(&im, 16);
+        assert_eq!(full.image_data(), &[0, 15, 8, 16]);
+    }
+
+    #[test]
+    fn test_owned_conversions() {
+        let rgb = OImage::<RGB8>::new(2, 1, 6, vec![255, 0, 0, 10, 10, 10]).unwrap();
+        let mono: OImage<Mono8> = rgb.clone().into();
+        assert_eq!(mono.image_data(), &[77, 10]);
+        let rgba: OImage<RGBA8> = rgb.clone().into();
+        assert_eq!(rgba.image_data(), &[255, 0, 0, 255, 10, 10, 10, 255]);
+        let rgb2: OImage<RGB8> = rgba.into();
+        assert_eq!(rgb2, rgb);
+        let rgb3: OImage<RGB8> = mono.into();
+        assert_eq!(rgb3.image_data(), &[77, 77, 77, 10, 10, 10]);
+
+        let be = OImage::<Mono16Be>::new(1, 1, 2, vec![1, 2]).unwrap();
+        let le: OImage<Mono16Le> = be.into();
+        assert_eq!(le.image_data(), &[2, 1]);
+        let be: OImage<Mono16Be> = le.into();
+        assert_eq!(be.image_data(), &[1, 2]);
+    }
+
+    #[test]
+    fn test_debayer_nearest() {
+        // (order, mosaic, expected pixels in raster order)
+        let cases = [
+            (
+                BayerOrder::RGGB,
+                [10, 20, 30, 40],
+                [[10, 20, 40], [10, 20, 40], [10, 30, 40], [10, 30, 40]],
+            ),
+            (
+                BayerOrder::BGGR,
+                [40, 20, 30, 10],
+                [[10, 20, 40], [10, 20, 40], [10, 30, 40], [10, 30, 40]],
+            ),
+            (
+                BayerOrder::GRBG,
+                [20, 10, 40, 30],
+                [[10, 20, 40], [10, 20, 40], [10, 30, 40], [10, 30, 40]],
+            ),
+            (
+                BayerOrder::GBRG,
+                [20, 40, 10, 30],
+                [[10, 20, 40], [10, 20, 40], [10, 30, 40], [10, 30, 40]],
+            ),
+        ];
+        for (order, mosaic, expected) in cases.iter() {
+            let im = ImageRef::<Mono8>::new(2, 2, 2, &mosaic[..]).unwrap();
+            let rgb = debayer(&im, *order, DemosaicMethod::Nearest);
+            assert_eq!((rgb.width(), rgb.height()), (2, 2));
+            let pixels: Vec<&[u8]> = rgb.image_data().chunks_exact(3).collect();
+            for (actual, expected) in pixels.iter().zip(expected.iter()) {
+                assert_eq!(actual, expected, "{:?}", order);
+            }
+        }
+    }
+
+    #[test]
+    fn test_debayer_bilinear() {
+        // A uniform mosaic produces a uniform gray image.
+        let mosaic = [100u8; 16];
+        let im = ImageRef::<Mono8>::new(4, 4, 4, &mosaic).unwrap();
+        let rgb = debayer(&im, BayerOrder::RGGB, DemosaicMethod::Bilinear);
+        assert!(rgb.image_data().iter().all(|x| *x == 100));
+
+        // Red at (0,0), (2,0), (0,2), (2,2); blue at (1,1) with value 40.
+        #[rustfmt::skip]
+        let mosaic = [
+            10, 20, 30, 20,
+            20, 40, 20, 40,
+            50, 20, 70, 20,
+            20, 40, 20, 40,
+        ];
+        let im = ImageRef::<Mono8>::new(4, 4, 4, &mosaic).unwrap();
+        let rgb = debayer(&im, BayerOrder::RGGB, DemosaicMethod::Bilinear);
+        // The blue pixel at (1,1) averages the four diagonal red samples.
+        let px = &rgb.image_data()[(4 + 1) * 3..(4 + 1) * 3 + 3];
+        assert_eq!(px, &[40, 20, 40]);
+    }
+}
@@ -0,0 +1,182 @@
+//! Images which are either mutably borrowed or owned
+
+use crate::{
+    image_ref::ImageRefMut, owned::OImage, ImageBufferMutRef, ImageBufferRef, ImageData,
+    ImageMutData, PixelFormat, Stride,
+};
+
+/// A mutable image which either borrows or owns its data.
+///
+/// This allows pipelines to modify a caller's buffer in place when possible
+/// and fall back to an owned copy otherwise.
+pub enum CowImageMut<'a, F: PixelFormat> {
+    /// Mutably borrowed image data.
+    BorrowedMut(ImageRefMut<'a, F>),
+    /// Owned image data.
+    Owned(OImage<F>),
+}
+
+impl<'a, F: PixelFormat> CowImageMut<'a, F> {
+    /// Whether the image data is owned.
+    pub fn is_owned(&self) -> bool {
+        matches!(self, CowImageMut::Owned(_))
+    }
+
+    /// Convert into an owned image, copying borrowed data.
+    pub fn owned(self) -> OImage<F> {
+        match self {
+            CowImageMut::BorrowedMut(im) => OImage::copy_from(&im),
+            CowImageMut::Owned(im) => im,
+        }
+    }
+
+    /// Get a mutable reference to the owned image, copying borrowed data
+    /// first.
+    ///
+    /// After this call, modifications no longer affect the originally borrowed
+    /// buffer.
+    pub fn make_owned(&mut self) -> &mut OImage<F> {
+        if let CowImageMut::BorrowedMut(im) = self {
+            let owned = OImage::copy_from(im);
+            *self = CowImageMut::Owned(owned);
+        }
+        match self {
+            CowImageMut::Owned(im) => im,
+            CowImageMut::BorrowedMut(_) => unreachable!(),
+        }
+    }
+}
+
+impl<'a, F: PixelFormat> From<ImageRefMut<'a, F>> for CowImageMut<'a, F> {
+    fn from(im: ImageRefMut<'a, F>) -> Self {
+        CowImageMut::BorrowedMut(im)
+    }
+}
+
+impl<F: PixelFormat> From<OImage<F>> for CowImageMut<'_, F> {
+    fn from(im: OImage<F>) -> Self {
+        CowImageMut::Owned(im)
+    }
+}
+
+impl<F: PixelFormat> OImage<F> {
+    /// Convert into an owned [CowImageMut].
+    pub fn into_cow_mut(self) -> CowImageMut<'static, F> {
+        CowImageMut::Owned(self)
+    }
+}
+
+impl<'a, F: PixelFormat> ImageRefMut<'a, F> {
+    /// Convert into a borrowed [CowImageMut].
+    pub fn into_cow_mut(self) -> CowImageMut<'a, F> {
+        CowImageMut::BorrowedMut(self)
+    }
+}
+
+impl<F: PixelFormat> std::fmt::Debug for CowImageMut<'_, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CowImageMut::BorrowedMut(im) => f.debug_tuple("BorrowedMut").field(im).finish(),
+            CowImageMut::Owned(im) => f.debug_tuple("Owned").field(im).finish(),
+        }
+    }
+}
+
+impl<F: PixelFormat> ImageData<F> for CowImageMut<'_, F> {
+    fn width(&self) -> u32 {
+        match self {
+            CowImageMut::BorrowedMut(im) => im.width(),
+            CowImageMut::Owned(im) => im.width(),
+        }
+    }
+    fn height(&self) -> u32 {
+        match self {
+            CowImageMut::BorrowedMut(im) => im.height(),
+            CowImageMut::Owned(im) => im.height(),
+        }
+    }
+    fn buffer_ref(&self) -> ImageBufferRef<'_, F> {
+        match self {
+            CowImageMut::BorrowedMut(im) => im.buffer_ref(),
+            CowImageMut::Owned(im) => im.buffer_ref(),
+        }
+    }
+    fn buffer(self) -> crate::ImageBuffer<F> {
+        match self {
+            CowImageMut::BorrowedMut(im) => im.buffer(),
+            CowImageMut::Owned(im) => im.buffer(),
+        }
+    }
+}
+
+impl<F: PixelFormat> ImageMutData<F> for CowImageMut<'_, F> {
+    fn buffer_mut_ref(&mut self) -> ImageBufferMutRef<'_, F> {
+        match self {
+            CowImageMut::BorrowedMut(im) => im.buffer_mut_ref(),
+            CowImageMut::Owned(im) => im.buffer_mut_ref(),
+        }
+    }
+}
+
+impl<F: PixelFormat> Stride for CowImageMut<'_, F> {
+    fn stride(&self) -> usize {
+        match self {
+            CowImageMut::BorrowedMut(im) => im.stride(),
+            CowImageMut::Owned(im) => im.stride(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{iter::HasRowMut, pixel_format::Mono8};
+
+    #[test]
+    fn test_borrowed_mut() {
+        let mut buf = [1, 2, 0, 3, 4];
+        {
+            let mut im: CowImageMut<Mono8> = ImageRefMut::new(2, 2, 3, &mut buf).unwrap().into();
+            assert!(!im.is_owned());
+            im.row_mut(1).unwrap()[0] = 30;
+        }
+        assert_eq!(buf, [1, 2, 0, 30, 4]);
+    }
+
+    #[test]
+    fn test_owned() {
+        let mut im: CowImageMut<Mono8> = OImage::new(2, 1, 2, vec![1, 2]).unwrap().into();
+        assert!(im.is_owned());
+        im.row_mut(0).unwrap()[1] = 20;
+        assert_eq!(im.owned().image_data(), &[1, 20]);
+    }
+
+    #[test]
+    fn test_make_owned() {
+        let mut buf = [1, 2, 0, 3, 4];
+        {
+            let mut im: CowImageMut<Mono8> = ImageRefMut::new(2, 2, 3, &mut buf).unwrap().into();
+            let owned = im.make_owned();
+            owned.row_mut(0).unwrap()[0] = 10;
+            assert!(im.is_owned());
+            assert_eq!(im.stride(), 3);
+            assert_eq!(im.owned().image_data(), &[10, 2, 0, 3, 4]);
+        }
+        // The borrowed buffer is not modified after the copy.
+        assert_eq!(buf, [1, 2, 0, 3, 4]);
+    }
+
+    #[test]
+    fn test_into_cow_mut() {
+        let im = OImage::<Mono8>::new(1, 1, 1, vec![1])
+            .unwrap()
+            .into_cow_mut();
+        assert!(im.is_owned());
+
+        let mut buf = [1u8];
+        let im = ImageRefMut::<Mono8>::new(1, 1, 1, &mut buf)
+            .unwrap()
+            .into_cow_mut();
+        assert!(!im.is_owned());
+    }
+}
@@ -0,0 +1,104 @@
+//! Drawing simple shapes into images
+
+use crate::{error::DrawError, iter::HasRowChunksExactMut, ImageMutStride, PixelFormat};
+
+/// Draw the 1 pixel wide outline of a rectangle.
+///
+/// The rectangle has its top-left corner at `(x, y)` and a size of `w` by
+/// `h` pixels. Parts outside the image are clipped. `color` holds the bytes of
+/// a single pixel and its length must equal the number of bytes per pixel.
+pub fn draw_rect<F: PixelFormat>(
+    img: &mut dyn ImageMutStride<F>,
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+    color: &[u8],
+) -> Result<(), DrawError> {
+    if F::BITS_PER_PIXEL % 8 != 0 {
+        return Err(DrawError::UnsupportedFormat);
+    }
+    let bytes_per_pixel = F::BITS_PER_PIXEL as usize / 8;
+    if color.len() != bytes_per_pixel {
+        return Err(DrawError::ColorLength {
+            expected: bytes_per_pixel,
+            actual: color.len(),
+        });
+    }
+    if w == 0 || h == 0 {
+        return Ok(());
+    }
+    // Inclusive bounds of the rectangle, computed in u64 to avoid overflow.
+    let (left, top) = (x as u64, y as u64);
+    let (right, bottom) = (left + w as u64 - 1, top + h as u64 - 1);
+    let width = img.width() as u64;
+    for (row_y, row) in img.rowchunks_exact_mut().enumerate() {
+        let row_y = row_y as u64;
+        if row_y < top || row_y > bottom {
+            continue;
+        }
+        let is_edge_row = row_y == top || row_y == bottom;
+        for (col, px) in row.chunks_exact_mut(bytes_per_pixel).enumerate() {
+            let col = col as u64;
+            if col >= width || col < left || col > right {
+                continue;
+            }
+            if is_edge_row || col == left || col == right {
+                px.copy_from_slice(color);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        image_ref::ImageRefMut,
+        pixel_format::{Mono8, RGB8},
+    };
+
+    #[test]
+    fn test_draw_rect_mono8() {
+        // 5x4 image with one byte of padding per row.
+        let mut buf = [0u8; 24];
+        {
+            let mut im = ImageRefMut::<Mono8>::new(5, 4, 6, &mut buf).unwrap();
+            draw_rect(&mut im, 1, 0, 3, 3, &[9]).unwrap();
+        }
+        #[rustfmt::skip]
+        let expected = [
+            0, 9, 9, 9, 0, 0,
+            0, 9, 0, 9, 0, 0,
+            0, 9, 9, 9, 0, 0,
+            0, 0, 0, 0, 0, 0,
+        ];
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_draw_rect_clipped() {
+        let mut buf = [0u8; 9];
+        {
+            let mut im = ImageRefMut::<Mono8>::new(3, 3, 3, &mut buf).unwrap();
+            draw_rect(&mut im, 1, 1, 10, 10, &[1]).unwrap();
+        }
+        assert_eq!(buf, [0, 0, 0, 0, 1, 1, 0, 1, 0]);
+    }
+
+    #[test]
+    fn test_draw_rect_rgb8() {
+        let mut buf = [0u8; 12];
+        let mut im = ImageRefMut::<RGB8>::new(2, 2, 6, &mut buf).unwrap();
+        assert_eq!(
+            draw_rect(&mut im, 0, 0, 2, 2, &[1, 2]),
+            Err(DrawError::ColorLength {
+                expected: 3,
+                actual: 2
+            })
+        );
+        draw_rect(&mut im, 0, 0, 1, 1, &[1, 2, 3]).unwrap();
+        assert_eq!(buf, [1, 2, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+}
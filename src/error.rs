@@ -0,0 +1,352 @@
+//! Error types
+
+/// The reason a buffer cannot store an image of the desired properties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BufferError {
+    /// The image height is zero.
+    ZeroHeight,
+    /// The buffer is smaller than required.
+    TooSmall {
+        /// The minimum number of bytes required.
+        required: usize,
+        /// The actual number of bytes in the buffer.
+        actual: usize,
+    },
+    /// The stride is smaller than the number of valid bytes in a row.
+    StrideTooSmall {
+        /// The number of valid bytes in a row.
+        required: usize,
+        /// The actual stride.
+        actual: usize,
+    },
+    /// The size required for the image does not fit in `usize`.
+    Overflow,
+}
+
+impl std::fmt::Display for BufferError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BufferError::ZeroHeight => write!(f, "image height is zero"),
+            BufferError::TooSmall { required, actual } => write!(
+                f,
+                "buffer too small: {} bytes required, {} bytes available",
+                required, actual
+            ),
+            BufferError::StrideTooSmall { required, actual } => write!(
+                f,
+                "stride too small: {} bytes required, stride is {} bytes",
+                required, actual
+            ),
+            BufferError::Overflow => write!(f, "required buffer size overflows"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferError {}
+
+/// An error converting a [crate::PixelFormat] type to a [crate::PixFmt].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PixFmtError {
+    /// The pixel format is not one of the formats defined in this crate.
+    Unregistered,
+}
+
+impl std::fmt::Display for PixFmtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PixFmtError::Unregistered => write!(
+                f,
+                "unknown PixelFormat implementation could not be converted to PixFmt"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PixFmtError {}
+
+/// An error blending two images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BlendError {
+    /// The foreground and background images have different dimensions.
+    DimensionMismatch,
+}
+
+impl std::fmt::Display for BlendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BlendError::DimensionMismatch => write!(
+                f,
+                "foreground and background images have different dimensions"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BlendError {}
+
+/// The images have different dimensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DimMismatch;
+
+impl std::fmt::Display for DimMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "images have different dimensions")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DimMismatch {}
+
+/// An error drawing into an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DrawError {
+    /// The pixel format does not use a whole number of bytes per pixel.
+    UnsupportedFormat,
+    /// The color does not have one byte per byte of a pixel.
+    ColorLength {
+        /// The number of bytes per pixel.
+        expected: usize,
+        /// The number of bytes in the color.
+        actual: usize,
+    },
+}
+
+impl std::fmt::Display for DrawError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DrawError::UnsupportedFormat => {
+                write!(f, "pixel format does not use whole bytes per pixel")
+            }
+            DrawError::ColorLength { expected, actual } => write!(
+                f,
+                "color has {} bytes, but pixels have {} bytes",
+                actual, expected
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DrawError {}
+
+/// An error concatenating images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConcatError {
+    /// No images were given.
+    Empty,
+    /// The images do not have matching dimensions along the joined edge.
+    DimensionMismatch,
+    /// The pixel format does not use a whole number of bytes per pixel.
+    UnsupportedFormat,
+    /// The size of the output image does not fit in `u32`.
+    Overflow,
+}
+
+impl std::fmt::Display for ConcatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ConcatError::Empty => write!(f, "no images to concatenate"),
+            ConcatError::DimensionMismatch => {
+                write!(f, "images have different dimensions along joined edge")
+            }
+            ConcatError::UnsupportedFormat => {
+                write!(f, "pixel format does not use whole bytes per pixel")
+            }
+            ConcatError::Overflow => write!(f, "concatenated image size overflows"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConcatError {}
+
+/// An error partitioning an image into equal blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PartitionError {
+    /// The number of columns or rows is zero.
+    ZeroCount,
+    /// The image width or height is not a positive multiple of the number of
+    /// columns or rows.
+    Indivisible,
+}
+
+impl std::fmt::Display for PartitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PartitionError::ZeroCount => write!(f, "number of columns or rows is zero"),
+            PartitionError::Indivisible => {
+                write!(f, "image size is not a multiple of the number of blocks")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PartitionError {}
+
+/// An error building an image row by row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BuildError {
+    /// The image height is zero.
+    ZeroHeight,
+    /// The stride is smaller than the number of valid bytes in a row.
+    StrideTooSmall,
+    /// A row does not have the number of valid bytes in a row.
+    RowLength {
+        /// The number of valid bytes in a row.
+        expected: usize,
+        /// The number of bytes in the pushed row.
+        actual: usize,
+    },
+    /// More rows than the image height were pushed.
+    TooManyRows,
+    /// Fewer rows than the image height were pushed.
+    TooFewRows {
+        /// The image height.
+        expected: u32,
+        /// The number of pushed rows.
+        actual: u32,
+    },
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BuildError::ZeroHeight => write!(f, "image height is zero"),
+            BuildError::StrideTooSmall => {
+                write!(f, "stride is smaller than the valid bytes in a row")
+            }
+            BuildError::RowLength { expected, actual } => write!(
+                f,
+                "row has {} bytes, but {} bytes are required",
+                actual, expected
+            ),
+            BuildError::TooManyRows => write!(f, "more rows than the image height"),
+            BuildError::TooFewRows { expected, actual } => {
+                write!(f, "{} of {} rows were pushed", actual, expected)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuildError {}
+
+/// An error copying one image into another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CopyError {
+    /// The source image is larger than the destination image.
+    DoesNotFit,
+    /// The source buffer does not hold exactly the valid bytes of the
+    /// destination.
+    SourceLength {
+        /// The number of valid bytes in the destination image.
+        expected: usize,
+        /// The length of the source buffer.
+        actual: usize,
+    },
+}
+
+impl std::fmt::Display for CopyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CopyError::DoesNotFit => {
+                write!(f, "source image does not fit in destination image")
+            }
+            CopyError::SourceLength { expected, actual } => {
+                write!(f, "source has {} bytes, expected {}", actual, expected)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CopyError {}
+
+/// An error appending rows to an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AppendError {
+    /// The appended data is not `num_rows * stride` bytes long.
+    Length {
+        /// The number of bytes required for the rows.
+        expected: usize,
+        /// The number of bytes given.
+        actual: usize,
+    },
+    /// The new height or buffer size does not fit in its type.
+    Overflow,
+}
+
+impl std::fmt::Display for AppendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AppendError::Length { expected, actual } => write!(
+                f,
+                "appended rows have {} bytes, expected {}",
+                actual, expected
+            ),
+            AppendError::Overflow => write!(f, "appended image size overflows"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AppendError {}
+
+/// The check which failed when validating an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// The image height is zero.
+    ZeroHeight,
+    /// The stride is smaller than the number of valid bytes in a row.
+    StrideTooSmall {
+        /// The stride of the image.
+        stride: usize,
+        /// The number of valid bytes in a row.
+        min_stride: usize,
+    },
+    /// Computing the required buffer size overflows.
+    Overflow,
+    /// The buffer is smaller than required.
+    BufferTooSmall {
+        /// The minimum number of bytes required.
+        required: usize,
+        /// The actual number of bytes in the buffer.
+        actual: usize,
+    },
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ValidationError::ZeroHeight => write!(f, "image height is zero"),
+            ValidationError::StrideTooSmall { stride, min_stride } => write!(
+                f,
+                "stride {} is smaller than the {} valid bytes in a row",
+                stride, min_stride
+            ),
+            ValidationError::Overflow => write!(f, "required buffer size overflows"),
+            ValidationError::BufferTooSmall { required, actual } => write!(
+                f,
+                "buffer too small: {} bytes required, {} bytes available",
+                required, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
@@ -0,0 +1,479 @@
+//! Geometric transformations of images
+//!
+//! The functions in this module copy the source image, respecting its stride,
+//! into a new, tightly packed [OImage].
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{
+    error::{ConcatError, PartitionError},
+    iter::{HasRow, HasRowChunksExact},
+    owned::OImage,
+    pixel_format::Mono8,
+    ImageStride, PixelFormat,
+};
+
+/// How tiles at the right and bottom edges of an image are handled when the
+/// image size is not a multiple of the tile size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileEdge {
+    /// Edge tiles are smaller than the requested tile size.
+    Clip,
+    /// Edge tiles have the requested tile size and are padded with zeros.
+    Pad,
+}
+
+/// An image which can be split into tiles.
+pub trait HasTiles<F>: ImageStride<F> {
+    /// Iterate over tiles of `tile_w` x `tile_h` pixels in raster order.
+    ///
+    /// Each tile is copied into a new, tightly packed [OImage], so memory use
+    /// is proportional to the tile size, not the image size. No tiles are
+    /// yielded if either tile dimension is zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pixel format does not use a whole number of bytes per
+    /// pixel.
+    fn tiles(&self, tile_w: u32, tile_h: u32, edge: TileEdge) -> Tiles<'_, F>;
+}
+
+impl<S, F> HasTiles<F> for S
+where
+    S: ImageStride<F> + ?Sized,
+    F: PixelFormat,
+{
+    fn tiles(&self, tile_w: u32, tile_h: u32, edge: TileEdge) -> Tiles<'_, F> {
+        assert!(
+            F::BITS_PER_PIXEL % 8 == 0,
+            "pixel format must use whole bytes per pixel"
+        );
+        let done = tile_w == 0 || tile_h == 0 || self.width() == 0 || self.height() == 0;
+        Tiles {
+            rows: self.rowchunks_exact().collect(),
+            width: self.width(),
+            tile_w,
+            tile_h,
+            edge,
+            x: 0,
+            y: 0,
+            done,
+            fmt: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Iterator over the tiles of an image.
+///
+/// See [HasTiles::tiles].
+pub struct Tiles<'a, F> {
+    rows: Vec<&'a [u8]>,
+    width: u32,
+    tile_w: u32,
+    tile_h: u32,
+    edge: TileEdge,
+    x: u32,
+    y: u32,
+    done: bool,
+    fmt: std::marker::PhantomData<F>,
+}
+
+impl<F> std::fmt::Debug for Tiles<'_, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Tiles")
+            .field("tile_w", &self.tile_w)
+            .field("tile_h", &self.tile_h)
+            .field("edge", &self.edge)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F: PixelFormat> Iterator for Tiles<'_, F> {
+    type Item = OImage<F>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let bytes_per_pixel = F::BITS_PER_PIXEL as usize / 8;
+        let height = self.rows.len() as u32;
+        let (w, h) = match self.edge {
+            TileEdge::Clip => (
+                self.tile_w.min(self.width - self.x),
+                self.tile_h.min(height - self.y),
+            ),
+            TileEdge::Pad => (self.tile_w, self.tile_h),
+        };
+        let stride = w as usize * bytes_per_pixel;
+        let mut buf = Vec::with_capacity(stride * h as usize);
+        let start = self.x as usize * bytes_per_pixel;
+        for row_idx in self.y as usize..(self.y + h) as usize {
+            let row_start = buf.len();
+            if let Some(row) = self.rows.get(row_idx) {
+                let end = (start + stride).min(row.len());
+                buf.extend_from_slice(&row[start..end]);
+            }
+            buf.resize(row_start + stride, 0);
+        }
+        let tile = OImage::from_parts(w, h, stride, buf);
+
+        self.x = self.x.saturating_add(self.tile_w);
+        if self.x >= self.width {
+            self.x = 0;
+            self.y = self.y.saturating_add(self.tile_h);
+            self.done = self.y >= height;
+        }
+        Some(tile)
+    }
+}
+
+/// Split an image into a grid of `cols` x `rows` equally sized blocks.
+///
+/// The blocks are returned in raster order. The image width and height must
+/// be multiples of `cols` and `rows`, respectively, and each block must hold
+/// at least one pixel.
+///
+/// # Panics
+///
+/// Panics if the pixel format does not use a whole number of bytes per pixel.
+pub fn partition<F: PixelFormat>(
+    src: &dyn ImageStride<F>,
+    cols: u32,
+    rows: u32,
+) -> Result<Vec<OImage<F>>, PartitionError> {
+    if cols == 0 || rows == 0 {
+        return Err(PartitionError::ZeroCount);
+    }
+    let (width, height) = (src.width(), src.height());
+    if width % cols != 0 || height % rows != 0 || width < cols || height < rows {
+        return Err(PartitionError::Indivisible);
+    }
+    Ok(src
+        .tiles(width / cols, height / rows, TileEdge::Clip)
+        .collect())
+}
+
+/// Sample an image at a fractional coordinate by bilinear interpolation.
+///
+/// Integer coordinates are pixel centers, so `(x, y) = (1.0, 2.0)` gives
+/// exactly the pixel in column 1 of row 2 and `(0.5, 0.5)` gives the average
+/// of the four top-left pixels. Returns None if the coordinate is outside the
+/// pixel centers, i.e. not within `0..=width-1` and `0..=height-1`, or is
+/// NaN.
+pub fn sample_bilinear_mono8(src: &dyn ImageStride<Mono8>, x: f32, y: f32) -> Option<f32> {
+    let max_x = src.width().checked_sub(1)? as f32;
+    let max_y = src.height().checked_sub(1)? as f32;
+    if !(x >= 0.0 && x <= max_x && y >= 0.0 && y <= max_y) {
+        return None;
+    }
+    // Truncation is the floor for non-negative values.
+    let (x0, y0) = (x as u32, y as u32);
+    let (x1, y1) = ((x0 + 1).min(max_x as u32), (y0 + 1).min(max_y as u32));
+    let (wx, wy) = (x - x0 as f32, y - y0 as f32);
+    let top = src.row(y0)?;
+    let bottom = src.row(y1)?;
+    let px = |row: &[u8], col: u32| row[col as usize] as f32;
+    let upper = px(top, x0) * (1.0 - wx) + px(top, x1) * wx;
+    let lower = px(bottom, x0) * (1.0 - wx) + px(bottom, x1) * wx;
+    Some(upper * (1.0 - wy) + lower * wy)
+}
+
+/// Build an image by sampling the source at given coordinates.
+///
+/// For output pixel `(x, y)`, the source is sampled with
+/// [sample_bilinear_mono8] at `(map_x[i], map_y[i])` where
+/// `i = y * out_w + x`. Coordinates outside the source give 0. This is the
+/// operation used for lens undistortion with a precomputed map. The output is
+/// tightly packed.
+///
+/// # Panics
+///
+/// Panics if `map_x` or `map_y` does not have `out_w * out_h` entries.
+pub fn remap_mono8(
+    src: &dyn ImageStride<Mono8>,
+    map_x: &[f32],
+    map_y: &[f32],
+    out_w: u32,
+    out_h: u32,
+) -> OImage<Mono8> {
+    let n_pixels = out_w as usize * out_h as usize;
+    assert_eq!(
+        map_x.len(),
+        n_pixels,
+        "map_x must have out_w * out_h entries"
+    );
+    assert_eq!(
+        map_y.len(),
+        n_pixels,
+        "map_y must have out_w * out_h entries"
+    );
+    let buf = map_x
+        .iter()
+        .zip(map_y.iter())
+        .map(|(x, y)| match sample_bilinear_mono8(src, *x, *y) {
+            Some(value) => (value + 0.5) as u8,
+            None => 0,
+        })
+        .collect();
+    OImage::from_parts(out_w, out_h, out_w as usize, buf)
+}
+
+/// Swap the rows and columns of an image.
+///
+/// The output has dimensions `height` x `width` with `out[x, y] = in[y, x]`.
+/// Unlike a rotation, the image is not flipped.
+pub fn transpose_mono8(src: &dyn ImageStride<Mono8>) -> OImage<Mono8> {
+    let rows: Vec<&[u8]> = src.rowchunks_exact().collect();
+    let out_width = src.height();
+    let out_height = src.width();
+    let mut buf = Vec::with_capacity(out_width as usize * out_height as usize);
+    for col in 0..out_height as usize {
+        buf.extend(rows.iter().map(|row| row[col]));
+    }
+    OImage::from_parts(out_width, out_height, out_width as usize, buf)
+}
+
+/// Place images next to each other, from left to right.
+///
+/// All images must have the same height. The output width is the sum of the
+/// input widths. Pixel formats which do not use a whole number of bytes per
+/// pixel are not supported.
+pub fn concat_horizontal<F: PixelFormat>(
+    imgs: &[&dyn ImageStride<F>],
+) -> Result<OImage<F>, ConcatError> {
+    if F::BITS_PER_PIXEL % 8 != 0 {
+        return Err(ConcatError::UnsupportedFormat);
+    }
+    let first = imgs.first().ok_or(ConcatError::Empty)?;
+    let height = first.height();
+    if imgs.iter().any(|im| im.height() != height) {
+        return Err(ConcatError::DimensionMismatch);
+    }
+    let width = imgs
+        .iter()
+        .try_fold(0u32, |sum, im| sum.checked_add(im.width()))
+        .ok_or(ConcatError::Overflow)?;
+    let stride = crate::min_stride::<F>(width);
+    let mut buf = Vec::with_capacity(stride * height as usize);
+    let mut rows: Vec<_> = imgs.iter().map(|im| im.rowchunks_exact()).collect();
+    for _ in 0..height {
+        for row in rows.iter_mut() {
+            if let Some(row) = row.next() {
+                buf.extend_from_slice(row);
+            }
+        }
+    }
+    Ok(OImage::from_parts(width, height, stride, buf))
+}
+
+/// Place images below each other, from top to bottom.
+///
+/// All images must have the same width. The output height is the sum of the
+/// input heights.
+pub fn concat_vertical<F: PixelFormat>(
+    imgs: &[&dyn ImageStride<F>],
+) -> Result<OImage<F>, ConcatError> {
+    let first = imgs.first().ok_or(ConcatError::Empty)?;
+    let width = first.width();
+    if imgs.iter().any(|im| im.width() != width) {
+        return Err(ConcatError::DimensionMismatch);
+    }
+    let height = imgs
+        .iter()
+        .try_fold(0u32, |sum, im| sum.checked_add(im.height()))
+        .ok_or(ConcatError::Overflow)?;
+    let stride = crate::min_stride::<F>(width);
+    let mut buf = Vec::with_capacity(stride * height as usize);
+    for im in imgs.iter() {
+        for row in im.rowchunks_exact() {
+            buf.extend_from_slice(row);
+        }
+    }
+    Ok(OImage::from_parts(width, height, stride, buf))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        image_ref::ImageRef,
+        pixel_format::{NV12, RGB8},
+        ImageData, Stride,
+    };
+
+    #[test]
+    fn test_sample_bilinear_mono8() {
+        let buf = [10, 20, 0, 30, 60, 0, 90, 0];
+        let im = ImageRef::<Mono8>::new(2, 3, 3, &buf).unwrap();
+        assert_eq!(sample_bilinear_mono8(&im, 0.0, 0.0), Some(10.0));
+        assert_eq!(sample_bilinear_mono8(&im, 1.0, 1.0), Some(60.0));
+        assert_eq!(sample_bilinear_mono8(&im, 0.0, 2.0), Some(90.0));
+        // The center of four pixels is their average.
+        assert_eq!(sample_bilinear_mono8(&im, 0.5, 0.5), Some(30.0));
+        assert_eq!(sample_bilinear_mono8(&im, 0.25, 0.0), Some(12.5));
+
+        assert_eq!(sample_bilinear_mono8(&im, 1.5, 0.0), None);
+        assert_eq!(sample_bilinear_mono8(&im, 0.0, -0.1), None);
+        assert_eq!(sample_bilinear_mono8(&im, f32::NAN, 0.0), None);
+    }
+
+    #[test]
+    fn test_remap_mono8() {
+        let buf: Vec<u8> = (0..12).map(|i| i * 10).collect();
+        let im = ImageRef::<Mono8>::new(3, 3, 4, &buf[..11]).unwrap();
+
+        let (map_x, map_y): (Vec<f32>, Vec<f32>) =
+            (0..9).map(|i| ((i % 3) as f32, (i / 3) as f32)).unzip();
+        let identity = remap_mono8(&im, &map_x, &map_y, 3, 3);
+        let rows: Vec<&[u8]> = im.rowchunks_exact().collect();
+        assert_eq!(identity.image_data(), &rows.concat()[..]);
+
+        // Shift right by half a pixel, with the last column out of bounds.
+        let shifted_x: Vec<f32> = map_x.iter().map(|x| x + 0.5).collect();
+        let shifted = remap_mono8(&im, &shifted_x, &map_y, 3, 3);
+        assert_eq!(&shifted.image_data()[..3], &[5, 15, 0]);
+    }
+
+    #[test]
+    fn test_transpose_mono8() {
+        // 3 wide, 2 high with one byte of padding per row.
+        let buf = [1, 2, 3, 0, 4, 5, 6];
+        let im = ImageRef::<Mono8>::new(3, 2, 4, &buf).unwrap();
+        let t = transpose_mono8(&im);
+        assert_eq!((t.width(), t.height(), t.stride()), (2, 3, 2));
+        assert_eq!(t.image_data(), &[1, 4, 2, 5, 3, 6]);
+
+        let tt = transpose_mono8(&t);
+        assert_eq!((tt.width(), tt.height()), (3, 2));
+        assert_eq!(tt.image_data(), &[1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_concat() {
+        let a_buf = [1, 2, 0, 3, 4];
+        let a = ImageRef::<Mono8>::new(2, 2, 3, &a_buf).unwrap();
+        let b_buf = [5, 6, 7, 8];
+        let b = ImageRef::<Mono8>::new(2, 2, 2, &b_buf).unwrap();
+
+        let h = concat_horizontal(&[&a, &b]).unwrap();
+        assert_eq!((h.width(), h.height(), h.stride()), (4, 2, 4));
+        assert_eq!(h.image_data(), &[1, 2, 5, 6, 3, 4, 7, 8]);
+
+        let v = concat_vertical(&[&a, &b]).unwrap();
+        assert_eq!((v.width(), v.height(), v.stride()), (2, 4, 2));
+        assert_eq!(v.image_data(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_concat_errors() {
+        let buf = [0u8; 6];
+        let a = ImageRef::<Mono8>::new(2, 3, 2, &buf).unwrap();
+        let b = ImageRef::<Mono8>::new(3, 2, 3, &buf).unwrap();
+        assert_eq!(
+            concat_horizontal::<Mono8>(&[]).unwrap_err(),
+            ConcatError::Empty
+        );
+        assert_eq!(
+            concat_horizontal(&[&a, &b]).unwrap_err(),
+            ConcatError::DimensionMismatch
+        );
+        assert_eq!(
+            concat_vertical(&[&a, &b]).unwrap_err(),
+            ConcatError::DimensionMismatch
+        );
+
+        let nv12 = ImageRef::<NV12>::new(2, 1, 3, &buf).unwrap();
+        assert_eq!(
+            concat_horizontal(&[&nv12, &nv12]).unwrap_err(),
+            ConcatError::UnsupportedFormat
+        );
+
+        let tall = ImageRef::<Mono8>::new(0, u32::MAX, 0, &buf).unwrap();
+        assert_eq!(
+            concat_vertical(&[&tall, &tall]).unwrap_err(),
+            ConcatError::Overflow
+        );
+    }
+
+    #[test]
+    fn test_concat_horizontal_overflow() {
+        // Dimensions only, for widths no real buffer could back.
+        struct Wide;
+        impl ImageData<Mono8> for Wide {
+            fn width(&self) -> u32 {
+                u32::MAX
+            }
+            fn height(&self) -> u32 {
+                1
+            }
+            fn buffer_ref(&self) -> crate::ImageBufferRef<'_, Mono8> {
+                crate::ImageBufferRef::new(&[])
+            }
+            fn buffer(self) -> crate::ImageBuffer<Mono8> {
+                crate::ImageBuffer::new(Vec::new())
+            }
+        }
+        impl Stride for Wide {
+            fn stride(&self) -> usize {
+                u32::MAX as usize
+            }
+        }
+        assert_eq!(
+            concat_horizontal(&[&Wide, &Wide]).unwrap_err(),
+            ConcatError::Overflow
+        );
+    }
+
+    #[test]
+    fn test_tiles() {
+        let buf: Vec<u8> = (0..16).collect();
+        let im = ImageRef::<Mono8>::new(4, 4, 4, &buf).unwrap();
+        let tiles: Vec<OImage<Mono8>> = im.tiles(2, 2, TileEdge::Clip).collect();
+        assert_eq!(tiles.len(), 4);
+        assert_eq!(tiles[0].image_data(), &[0, 1, 4, 5]);
+        assert_eq!(tiles[1].image_data(), &[2, 3, 6, 7]);
+        assert_eq!(tiles[2].image_data(), &[8, 9, 12, 13]);
+        assert_eq!(tiles[3].image_data(), &[10, 11, 14, 15]);
+        assert_eq!(im.tiles(0, 2, TileEdge::Clip).count(), 0);
+    }
+
+    #[test]
+    fn test_partition() {
+        let buf: Vec<u8> = (0..16).collect();
+        let im = ImageRef::<Mono8>::new(4, 4, 4, &buf).unwrap();
+        let blocks = partition(&im, 2, 2).unwrap();
+        assert_eq!(blocks.len(), 4);
+        assert!(blocks.iter().all(|b| (b.width(), b.height()) == (2, 2)));
+        assert_eq!(blocks[0].image_data(), &[0, 1, 4, 5]);
+        assert_eq!(blocks[1].image_data(), &[2, 3, 6, 7]);
+        assert_eq!(blocks[2].image_data(), &[8, 9, 12, 13]);
+        assert_eq!(blocks[3].image_data(), &[10, 11, 14, 15]);
+
+        assert_eq!(partition(&im, 4, 1).unwrap().len(), 4);
+        assert_eq!(partition(&im, 3, 2), Err(PartitionError::Indivisible));
+        assert_eq!(partition(&im, 8, 1), Err(PartitionError::Indivisible));
+        assert_eq!(partition(&im, 0, 2), Err(PartitionError::ZeroCount));
+    }
+
+    #[test]
+    fn test_tiles_edges() {
+        // 3x3 RGB8 image split into 2x2 tiles.
+        let buf: Vec<u8> = (0..27).collect();
+        let im = ImageRef::<RGB8>::new(3, 3, 9, &buf).unwrap();
+
+        let clipped: Vec<OImage<RGB8>> = im.tiles(2, 2, TileEdge::Clip).collect();
+        let sizes: Vec<(u32, u32)> = clipped.iter().map(|t| (t.width(), t.height())).collect();
+        assert_eq!(sizes, vec![(2, 2), (1, 2), (2, 1), (1, 1)]);
+        assert_eq!(clipped[1].image_data(), &[6, 7, 8, 15, 16, 17]);
+        assert_eq!(clipped[3].image_data(), &[24, 25, 26]);
+
+        let padded: Vec<OImage<RGB8>> = im.tiles(2, 2, TileEdge::Pad).collect();
+        assert!(padded.iter().all(|t| (t.width(), t.height()) == (2, 2)));
+        assert_eq!(padded[3].stride(), 6);
+        assert_eq!(
+            padded[3].image_data(),
+            &[24, 25, 26, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+}
@@ -1,9 +1,38 @@
 //! References to image data
 
-use crate::{ImageBufferMutRef, ImageBufferRef, ImageData, ImageMutData, PixelFormat, Stride};
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::vec::Vec;
+
+use crate::{
+    error::CopyError,
+    iter::{HasRowChunksExact, HasRowChunksExactMut, RowChunksExact},
+    pixel_format, ImageBufferMutRef, ImageBufferRef, ImageData, ImageMutData, PixFmt, PixelFormat,
+    Stride,
+};
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+use crate::{owned::OImage, pixel_format::Mono8};
 
 // -----
 
+/// Infer the stride of a buffer holding an image with known dimensions.
+///
+/// This assumes every row, including the last, has the same length, as in
+/// raw dumps from tools that do not report the stride. Returns
+/// `buf_len / height` if `buf_len` is a multiple of `height` and the result
+/// holds the valid bytes of a row, otherwise None.
+pub fn infer_stride<F: PixelFormat>(width: u32, height: u32, buf_len: usize) -> Option<usize> {
+    if height == 0 || buf_len % height as usize != 0 {
+        return None;
+    }
+    let stride = buf_len / height as usize;
+    let min_stride = crate::min_stride::<F>(width);
+    if stride < min_stride {
+        return None;
+    }
+    Some(stride)
+}
+
 /// A view of image to have pixel format `FMT`.
 pub struct ImageRef<'a, FMT: PixelFormat> {
     buf: &'a [u8],
@@ -16,16 +45,16 @@ pub struct ImageRef<'a, FMT: PixelFormat> {
 impl<'a, FMT: PixelFormat> ImageRef<'a, FMT> {
     /// Use a `&[u8]` slice as the backing store for an ImageRef.
     ///
-    /// Returns None if the buffer is not large enough to store an image of the
-    /// desired properties.
+    /// Returns None if the stride is smaller than the valid bytes of a row or
+    /// if the buffer is not large enough to store an image of the desired
+    /// properties.
     pub fn new(width: u32, height: u32, stride: usize, buf: &'a [u8]) -> Option<Self> {
-        let fmt = crate::pixel_format::pixfmt::<FMT>().unwrap();
-        let min_stride = fmt.bits_per_pixel() as usize * width as usize / 8;
+        let min_stride = crate::min_stride::<FMT>(width);
 
-        if height == 0 {
+        if height == 0 || stride < min_stride {
             return None;
         }
-        let sz = stride * (height as usize - 1) + min_stride;
+        let sz = crate::roi_byte_offset(0, height - 1, stride, 0)?.checked_add(min_stride)?;
 
         if buf.len() < sz {
             return None;
@@ -38,6 +67,81 @@ impl<'a, FMT: PixelFormat> ImageRef<'a, FMT> {
             fmt: std::marker::PhantomData,
         })
     }
+
+    /// Use a `&[u8]` slice of tightly packed rows as the backing store
+    /// for an ImageRef.
+    ///
+    /// The stride is `width` times the number of bytes per pixel. Returns None
+    /// for pixel formats which are not a whole number of bytes per pixel or if
+    /// the buffer is not large enough.
+    pub fn new_packed(width: u32, height: u32, buf: &'a [u8]) -> Option<Self> {
+        if FMT::BITS_PER_PIXEL % 8 != 0 {
+            return None;
+        }
+        let stride = width as usize * (FMT::BITS_PER_PIXEL / 8) as usize;
+        Self::new(width, height, stride, buf)
+    }
+
+    /// Use a fixed-size array as the backing store for an image.
+    ///
+    /// This is the same as [ImageRef::new] but avoids slicing the array, which
+    /// is convenient for static test images without an allocator.
+    pub fn from_array<const N: usize>(
+        width: u32,
+        height: u32,
+        stride: usize,
+        arr: &'a [u8; N],
+    ) -> Option<Self> {
+        Self::new(width, height, stride, arr)
+    }
+
+    /// Create a view of a single row, such as a line-scan camera frame.
+    ///
+    /// The image has height 1 and a stride equal to the buffer length. Returns
+    /// None if the buffer is too small for `width` pixels.
+    pub fn single_row(width: u32, buf: &'a [u8]) -> Option<Self> {
+        Self::new(width, 1, buf.len(), buf)
+    }
+
+    /// A view of `count` consecutive rows starting at row `start`.
+    ///
+    /// The view has the same width and stride. Returns None if `count` is zero
+    /// or the rows extend past the end of the image.
+    pub fn rows(&self, start: u32, count: u32) -> Option<ImageRef<'_, FMT>> {
+        if count == 0 || start.checked_add(count)? > self.height {
+            return None;
+        }
+        let offset = crate::roi_byte_offset(0, start, self.stride, 0)?;
+        ImageRef::new(self.width, count, self.stride, self.buf.get(offset..)?)
+    }
+
+    /// A view of the `w` by `h` region with top-left corner at `(x, y)`.
+    ///
+    /// The view has the same stride. Returns None if the region is empty or
+    /// extends past the image, or if pixels of the format do not start on
+    /// byte boundaries.
+    pub fn roi(&self, x: u32, y: u32, w: u32, h: u32) -> Option<ImageRef<'_, FMT>> {
+        if FMT::BITS_PER_PIXEL % 8 != 0 || w == 0 || h == 0 {
+            return None;
+        }
+        if x.checked_add(w)? > self.width || y.checked_add(h)? > self.height {
+            return None;
+        }
+        let bytes_per_pixel = FMT::BITS_PER_PIXEL as usize / 8;
+        let offset = crate::roi_byte_offset(x, y, self.stride, bytes_per_pixel)?;
+        ImageRef::new(w, h, self.stride, self.buf.get(offset..)?)
+    }
+
+    /// A view of the `w` by `h` region at the center of the image.
+    ///
+    /// When the margins cannot be split evenly, the extra column or row is
+    /// left on the right or bottom. Returns None if the region is larger than
+    /// the image or otherwise not allowed by [ImageRef::roi].
+    pub fn crop_centered(&self, w: u32, h: u32) -> Option<ImageRef<'_, FMT>> {
+        let x = self.width.checked_sub(w)? / 2;
+        let y = self.height.checked_sub(h)? / 2;
+        self.roi(x, y, w, h)
+    }
 }
 
 impl<F: PixelFormat> std::fmt::Debug for ImageRef<'_, F> {
@@ -51,6 +155,12 @@ impl<F: PixelFormat> std::fmt::Debug for ImageRef<'_, F> {
     }
 }
 
+impl<F: PixelFormat> std::fmt::Display for ImageRef<'_, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        crate::fmt_summary::<F>(f, self.width, self.height, self.stride)
+    }
+}
+
 impl<'a, FMT: PixelFormat> ImageData<FMT> for ImageRef<'a, FMT> {
     fn width(&self) -> u32 {
         self.width
@@ -74,6 +184,40 @@ impl<'a, FMT: PixelFormat> Stride for ImageRef<'a, FMT> {
     }
 }
 
+/// Iterate over the valid bytes of each row.
+impl<'b, 'a, FMT: PixelFormat> IntoIterator for &'b ImageRef<'a, FMT> {
+    type Item = &'b [u8];
+    type IntoIter = RowChunksExact<'b>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.rowchunks_exact()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> ImageRef<'a, Mono8> {
+    /// Copy every `factor`-th pixel of every `factor`-th row into a new image.
+    ///
+    /// This is nearest-neighbor decimation, so arbitrary factors are supported
+    /// and no averaging is done. The result is tightly packed with dimensions
+    /// `ceil(width/factor)` x `ceil(height/factor)`.
+    ///
+    /// Returns None if `factor` is zero.
+    pub fn decimate(&self, factor: u32) -> Option<OImage<Mono8>> {
+        if factor == 0 {
+            return None;
+        }
+        let ceil_div = |x: u32| if x == 0 { 0 } else { (x - 1) / factor + 1 };
+        let width = ceil_div(self.width);
+        let height = ceil_div(self.height);
+        let step = factor as usize;
+        let mut buf = Vec::with_capacity(width as usize * height as usize);
+        for row in self.rowchunks_exact().step_by(step) {
+            buf.extend(row.iter().step_by(step));
+        }
+        Some(OImage::from_parts(width, height, width as usize, buf))
+    }
+}
+
 // -----
 
 /// A view of mutable image to have pixel format `FMT`.
@@ -88,16 +232,16 @@ pub struct ImageRefMut<'a, FMT: PixelFormat> {
 impl<'a, FMT: PixelFormat> ImageRefMut<'a, FMT> {
     /// Use a `&mut [u8]` slice as the backing store for an ImageRefMut.
     ///
-    /// Returns None if the buffer is not large enough to store an image of the
-    /// desired properties.
+    /// Returns None if the stride is smaller than the valid bytes of a row or
+    /// if the buffer is not large enough to store an image of the desired
+    /// properties.
     pub fn new(width: u32, height: u32, stride: usize, buf: &'a mut [u8]) -> Option<Self> {
-        let fmt = crate::pixel_format::pixfmt::<FMT>().unwrap();
-        let min_stride = fmt.bits_per_pixel() as usize * width as usize / 8;
+        let min_stride = crate::min_stride::<FMT>(width);
 
-        if height == 0 {
+        if height == 0 || stride < min_stride {
             return None;
         }
-        let sz = stride * (height as usize - 1) + min_stride;
+        let sz = crate::roi_byte_offset(0, height - 1, stride, 0)?.checked_add(min_stride)?;
 
         if buf.len() < sz {
             return None;
@@ -110,6 +254,134 @@ impl<'a, FMT: PixelFormat> ImageRefMut<'a, FMT> {
             fmt: std::marker::PhantomData,
         })
     }
+
+    /// Use a `&mut [u8]` slice of tightly packed rows as the backing store
+    /// for an ImageRefMut.
+    ///
+    /// The stride is `width` times the number of bytes per pixel. Returns None
+    /// for pixel formats which are not a whole number of bytes per pixel or if
+    /// the buffer is not large enough.
+    pub fn new_packed(width: u32, height: u32, buf: &'a mut [u8]) -> Option<Self> {
+        if FMT::BITS_PER_PIXEL % 8 != 0 {
+            return None;
+        }
+        let stride = width as usize * (FMT::BITS_PER_PIXEL / 8) as usize;
+        Self::new(width, height, stride, buf)
+    }
+
+    /// A mutable view of `count` consecutive rows starting at row `start`.
+    ///
+    /// The view has the same width and stride. Returns None if `count` is zero
+    /// or the rows extend past the end of the image.
+    pub fn rows_mut(&mut self, start: u32, count: u32) -> Option<ImageRefMut<'_, FMT>> {
+        if count == 0 || start.checked_add(count)? > self.height {
+            return None;
+        }
+        let offset = crate::roi_byte_offset(0, start, self.stride, 0)?;
+        let buf = self.buf.get_mut(offset..)?;
+        ImageRefMut::new(self.width, count, self.stride, buf)
+    }
+
+    /// Fill the image from a tightly packed buffer.
+    ///
+    /// `src` holds the rows one after another without padding. Each row is
+    /// copied into the valid bytes of the corresponding row of this image,
+    /// leaving the padding untouched. Returns an error if `src` is not exactly
+    /// the number of valid bytes in a row times the height.
+    pub fn copy_from_packed(&mut self, src: &[u8]) -> Result<(), CopyError> {
+        let valid_stride = crate::min_stride::<FMT>(self.width);
+        let expected = valid_stride * self.height as usize;
+        if src.len() != expected {
+            return Err(CopyError::SourceLength {
+                expected,
+                actual: src.len(),
+            });
+        }
+        if valid_stride == 0 {
+            return Ok(());
+        }
+        for (dst_row, src_row) in self
+            .rowchunks_exact_mut()
+            .zip(src.chunks_exact(valid_stride))
+        {
+            dst_row.copy_from_slice(src_row);
+        }
+        Ok(())
+    }
+}
+
+impl<'a, FMT: PixelFormat> ImageRefMut<'a, FMT> {
+    /// Borrow as a read-only view with the same geometry.
+    ///
+    /// This does not copy the data and the mutable view remains usable once
+    /// the returned view is dropped.
+    pub fn as_ref(&self) -> ImageRef<'_, FMT> {
+        ImageRef {
+            buf: self.buf,
+            width: self.width,
+            height: self.height,
+            stride: self.stride,
+            fmt: std::marker::PhantomData,
+        }
+    }
+
+    /// Reborrow as a shorter-lived mutable view with the same geometry.
+    ///
+    /// This is useful to pass the view to a function taking an `ImageRefMut`
+    /// by value without giving up this one.
+    pub fn reborrow_mut(&mut self) -> ImageRefMut<'_, FMT> {
+        ImageRefMut {
+            buf: self.buf,
+            width: self.width,
+            height: self.height,
+            stride: self.stride,
+            fmt: std::marker::PhantomData,
+        }
+    }
+
+    /// Split into two non-overlapping views at row `row`.
+    ///
+    /// The first view contains rows `0..row` and the second contains rows
+    /// `row..height`. Both have the same width and stride as the original, so
+    /// they can be processed independently (e.g. on different threads).
+    ///
+    /// If `row` is 0 or `height`, one of the views is empty: it has a height of
+    /// zero and so is not a valid image, which [ImageRefMut::new] would never
+    /// return. It has no rows to iterate and
+    /// [Validate::validate](crate::Validate::validate) reports it as
+    /// `ZeroHeight`.
+    ///
+    /// Returns None if `row > height` or if the row offset overflows.
+    pub fn split_at_row(self, row: u32) -> Option<(Self, Self)> {
+        let Self {
+            buf,
+            width,
+            height,
+            stride,
+            fmt,
+        } = self;
+        if row > height {
+            return None;
+        }
+        let mid = crate::roi_byte_offset(0, row, stride, 0)?.min(buf.len());
+        let (top, bottom) = buf.split_at_mut(mid);
+        Some((
+            Self {
+                buf: top,
+                width,
+                height: row,
+                stride,
+                fmt,
+            },
+            Self {
+                buf: bottom,
+                width,
+                height: height - row,
+                stride,
+                fmt,
+            },
+        ))
+    }
 }
 
 impl<F: PixelFormat> std::fmt::Debug for ImageRefMut<'_, F> {
@@ -123,6 +395,12 @@ impl<F: PixelFormat> std::fmt::Debug for ImageRefMut<'_, F> {
     }
 }
 
+impl<F: PixelFormat> std::fmt::Display for ImageRefMut<'_, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        crate::fmt_summary::<F>(f, self.width, self.height, self.stride)
+    }
+}
+
 impl<'a, FMT: PixelFormat> ImageData<FMT> for ImageRefMut<'a, FMT> {
     fn width(&self) -> u32 {
         self.width
@@ -151,3 +429,369 @@ impl<'a, FMT: PixelFormat> Stride for ImageRefMut<'a, FMT> {
         self.stride
     }
 }
+
+// -----
+
+macro_rules! define_any_image_ref {
+    ($($name:ident),*) => {
+        /// A view of image data whose pixel format is only known at runtime.
+        ///
+        /// This bridges dynamic format codes, as used in C APIs, into the typed
+        /// [ImageRef]. Use [AnyImageRef::downcast] to get the typed view.
+        #[derive(Debug)]
+        #[non_exhaustive]
+        #[allow(non_camel_case_types)]
+        pub enum AnyImageRef<'a> {
+            $($name(ImageRef<'a, pixel_format::$name>),)*
+        }
+
+        /// Use a `&[u8]` slice as the backing store for an image with the
+        /// runtime pixel format `fmt`.
+        ///
+        /// Returns None if the buffer is not large enough to store an image of
+        /// the desired properties.
+        pub fn any_image_ref(
+            fmt: PixFmt,
+            width: u32,
+            height: u32,
+            stride: usize,
+            buf: &[u8],
+        ) -> Option<AnyImageRef<'_>> {
+            Some(match fmt {
+                $(PixFmt::$name => AnyImageRef::$name(ImageRef::new(width, height, stride, buf)?),)*
+            })
+        }
+
+        impl<'a> AnyImageRef<'a> {
+            /// The runtime pixel format.
+            pub fn pixel_format(&self) -> PixFmt {
+                match self {
+                    $(AnyImageRef::$name(_) => PixFmt::$name,)*
+                }
+            }
+
+            /// The buffer, width, height and stride.
+            fn parts(&self) -> (&'a [u8], u32, u32, usize) {
+                match self {
+                    $(AnyImageRef::$name(im) => (im.buf, im.width, im.height, im.stride),)*
+                }
+            }
+        }
+    };
+}
+
+define_any_image_ref!(
+    Mono8,
+    Mono16,
+    Mono16Le,
+    Mono16Be,
+    Mono32f,
+    RGB8,
+    RGBA8,
+    BayerRG8,
+    BayerRG32f,
+    BayerBG8,
+    BayerBG32f,
+    BayerGB8,
+    BayerGB32f,
+    BayerGR8,
+    BayerGR32f,
+    YUV444,
+    YUV422,
+    NV12,
+    I420,
+    Coord3D_ABC32f
+);
+
+impl<'a> AnyImageRef<'a> {
+    /// Convert to a view with the static pixel format `F`.
+    ///
+    /// Returns None if `F` is not the runtime pixel format of this image.
+    pub fn downcast<F: PixelFormat>(&self) -> Option<ImageRef<'a, F>> {
+        if pixel_format::pixfmt::<F>().ok()? != self.pixel_format() {
+            return None;
+        }
+        let (buf, width, height, stride) = self.parts();
+        Some(ImageRef {
+            buf,
+            width,
+            height,
+            stride,
+            fmt: std::marker::PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        iter::HasRowChunksExactMut,
+        pixel_format::{NV12, RGB8},
+    };
+
+    #[test]
+    fn test_into_iter_rows() {
+        let buf = [1, 2, 0, 3, 4, 0];
+        let im = ImageRef::<Mono8>::new(2, 2, 3, &buf).unwrap();
+        let mut rows = Vec::new();
+        for row in &im {
+            rows.push(row);
+        }
+        assert_eq!(rows, vec![&[1, 2][..], &[3, 4][..]]);
+    }
+
+    #[test]
+    fn test_decimate() {
+        const STRIDE: usize = 12;
+        let mut image_data = vec![0u8; STRIDE * 10];
+        for row in 0..10 {
+            for col in 0..10 {
+                image_data[row * STRIDE + col] = (row * 10 + col) as u8;
+            }
+        }
+        let im = ImageRef::<Mono8>::new(10, 10, STRIDE, &image_data).unwrap();
+        assert!(im.decimate(0).is_none());
+
+        let small = im.decimate(3).unwrap();
+        assert_eq!(small.width(), 4);
+        assert_eq!(small.height(), 4);
+        assert_eq!(small.stride(), 4);
+        #[rustfmt::skip]
+        assert_eq!(
+            small.image_data(),
+            &[
+                0, 3, 6, 9,
+                30, 33, 36, 39,
+                60, 63, 66, 69,
+                90, 93, 96, 99,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_any_image_ref() {
+        let buf = [1, 2, 3, 4, 5, 6];
+        let any = any_image_ref(PixFmt::Mono8, 3, 2, 3, &buf).unwrap();
+        assert_eq!(any.pixel_format(), PixFmt::Mono8);
+        assert!(any.downcast::<RGB8>().is_none());
+        let im = any.downcast::<Mono8>().unwrap();
+        assert_eq!((im.width(), im.height(), im.stride()), (3, 2, 3));
+        assert_eq!(im.image_data(), &buf);
+
+        let any = any_image_ref(PixFmt::RGB8, 2, 1, 6, &buf).unwrap();
+        assert_eq!(any.pixel_format(), PixFmt::RGB8);
+        assert!(any.downcast::<Mono8>().is_none());
+        let im = any.downcast::<RGB8>().unwrap();
+        assert_eq!(im.width(), 2);
+
+        assert!(any_image_ref(PixFmt::RGB8, 3, 1, 9, &buf).is_none());
+    }
+
+    #[test]
+    fn test_stride_too_small() {
+        // The buffer is large enough, but rows would overlap.
+        let mut buf = [0u8; 12];
+        assert!(ImageRef::<RGB8>::new(2, 2, 5, &buf).is_none());
+        assert!(ImageRefMut::<RGB8>::new(2, 2, 5, &mut buf).is_none());
+        assert!(ImageRef::<RGB8>::new(2, 2, 6, &buf).is_some());
+    }
+
+    #[test]
+    fn test_stride_overflow() {
+        let mut buf = [0u8; 12];
+        let stride = usize::MAX - 2;
+        assert!(ImageRef::<RGB8>::new(2, 2, stride, &buf).is_none());
+        assert!(ImageRefMut::<RGB8>::new(2, 3, stride, &mut buf).is_none());
+    }
+
+    #[test]
+    fn test_min_stride_rounds_up() {
+        // 3 pixels of a 12 bits per pixel format need 36 bits, so 5 bytes.
+        assert!(ImageRef::<NV12>::new(3, 1, 5, &[0u8; 4]).is_none());
+        let im = ImageRef::<NV12>::new(3, 2, 5, &[0u8; 10]).unwrap();
+        assert_eq!(im.rowchunks_exact().next().unwrap().len(), 5);
+        assert_eq!(infer_stride::<NV12>(3, 2, 8), None);
+    }
+
+    #[test]
+    fn test_rows() {
+        let mut buf: Vec<u8> = (0..40).collect();
+        let im = ImageRef::<Mono8>::new(3, 10, 4, &buf).unwrap();
+        let band = im.rows(4, 3).unwrap();
+        assert_eq!((band.width(), band.height(), band.stride()), (3, 3, 4));
+        let rows: Vec<&[u8]> = band.rowchunks_exact().collect();
+        assert_eq!(rows, vec![&[16, 17, 18][..], &[20, 21, 22], &[24, 25, 26]]);
+        assert!(im.rows(9, 1).is_some());
+        assert!(im.rows(9, 2).is_none());
+        assert!(im.rows(2, 0).is_none());
+        assert!(im.rows(u32::MAX, 2).is_none());
+
+        let mut im = ImageRefMut::<Mono8>::new(3, 10, 4, &mut buf).unwrap();
+        let mut band = im.rows_mut(4, 3).unwrap();
+        band.rowchunks_exact_mut().next().unwrap()[0] = 255;
+        assert!(im.rows_mut(8, 3).is_none());
+        assert_eq!(buf[16], 255);
+    }
+
+    #[test]
+    fn test_copy_from_packed() {
+        let mut buf = [9u8; 11];
+        let mut im = ImageRefMut::<Mono8>::new(3, 3, 4, &mut buf).unwrap();
+        im.copy_from_packed(&[1, 2, 3, 4, 5, 6, 7, 8, 9]).unwrap();
+        assert_eq!(
+            im.copy_from_packed(&[1, 2, 3]),
+            Err(CopyError::SourceLength {
+                expected: 9,
+                actual: 3
+            })
+        );
+        assert_eq!(buf, [1, 2, 3, 9, 4, 5, 6, 9, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_from_array() {
+        // No allocation is needed.
+        static PIXELS: [u8; 5] = [1, 2, 0, 3, 4];
+        let im = ImageRef::<Mono8>::from_array(2, 2, 3, &PIXELS).unwrap();
+        assert_eq!((im.width(), im.height(), im.stride()), (2, 2, 3));
+        let mut rows = im.rowchunks_exact();
+        assert_eq!(rows.next(), Some(&[1, 2][..]));
+        assert_eq!(rows.next(), Some(&[3, 4][..]));
+        assert!(ImageRef::<Mono8>::from_array(2, 3, 3, &PIXELS).is_none());
+    }
+
+    #[test]
+    fn test_single_row() {
+        let buf = [1, 2, 3, 4, 5, 6, 0];
+        let im = ImageRef::<RGB8>::single_row(2, &buf).unwrap();
+        assert_eq!((im.width(), im.height(), im.stride()), (2, 1, 7));
+        let rows: Vec<&[u8]> = im.rowchunks_exact().collect();
+        assert_eq!(rows, vec![&[1, 2, 3, 4, 5, 6][..]]);
+        assert!(ImageRef::<RGB8>::single_row(3, &buf).is_none());
+    }
+
+    #[test]
+    fn test_roi() {
+        let buf: Vec<u8> = (0..30).collect();
+        let im = ImageRef::<RGB8>::new(3, 3, 10, &buf).unwrap();
+        let roi = im.roi(1, 1, 2, 2).unwrap();
+        assert_eq!((roi.width(), roi.height(), roi.stride()), (2, 2, 10));
+        let rows: Vec<&[u8]> = roi.rowchunks_exact().collect();
+        assert_eq!(
+            rows,
+            vec![&[13, 14, 15, 16, 17, 18][..], &[23, 24, 25, 26, 27, 28]]
+        );
+        assert!(im.roi(2, 2, 1, 1).is_some());
+        assert!(im.roi(2, 2, 2, 1).is_none());
+        assert!(im.roi(0, 0, 0, 1).is_none());
+        assert!(im.roi(u32::MAX, 0, 1, 1).is_none());
+    }
+
+    #[test]
+    fn test_crop_centered() {
+        let buf: Vec<u8> = (0..100).collect();
+        let im = ImageRef::<Mono8>::new(10, 10, 10, &buf).unwrap();
+        let crop = im.crop_centered(4, 4).unwrap();
+        assert_eq!((crop.width(), crop.height()), (4, 4));
+        // The top-left pixel is at (3, 3).
+        assert_eq!(crop.image_data()[0], 33);
+        let rows: Vec<&[u8]> = crop.rowchunks_exact().collect();
+        assert_eq!(rows[3], &[63, 64, 65, 66]);
+
+        assert_eq!(im.crop_centered(10, 10).unwrap().image_data(), &buf[..]);
+        assert!(im.crop_centered(11, 4).is_none());
+        assert!(im.crop_centered(4, 11).is_none());
+    }
+
+    #[test]
+    fn test_infer_stride() {
+        assert_eq!(infer_stride::<RGB8>(4, 3, 36), Some(12));
+        assert_eq!(infer_stride::<RGB8>(4, 3, 48), Some(16));
+        assert_eq!(infer_stride::<RGB8>(4, 3, 37), None);
+        assert_eq!(infer_stride::<RGB8>(4, 3, 33), None);
+        assert_eq!(infer_stride::<RGB8>(4, 0, 0), None);
+    }
+
+    #[test]
+    fn test_display() {
+        let buf = [0u8; 12];
+        let im = ImageRef::<RGB8>::new(2, 2, 6, &buf).unwrap();
+        assert_eq!(format!("{}", im), "RGB8 2x2 stride=6");
+        let mut buf = [0u8; 5];
+        let im = ImageRefMut::<Mono8>::new(2, 2, 3, &mut buf).unwrap();
+        assert_eq!(format!("{}", im), "Mono8 2x2 stride=3");
+    }
+
+    #[test]
+    fn test_as_ref_and_reborrow_mut() {
+        let mut buf = [1, 2, 0, 3, 4, 0];
+        let mut im = ImageRefMut::<Mono8>::new(2, 2, 3, &mut buf).unwrap();
+        {
+            let view = im.as_ref();
+            assert_eq!((view.width(), view.height(), view.stride()), (2, 2, 3));
+            let rows: Vec<&[u8]> = view.rowchunks_exact().collect();
+            assert_eq!(rows, vec![&[1, 2][..], &[3, 4][..]]);
+        }
+        {
+            let (top, _bottom) = im.reborrow_mut().split_at_row(1).unwrap();
+            top.buf[0] = 10;
+        }
+        // The original handle is still usable after both borrows end.
+        im.rowchunks_exact_mut().nth(1).unwrap()[1] = 40;
+        assert_eq!(buf, [10, 2, 0, 3, 40, 0]);
+    }
+
+    #[test]
+    fn test_split_at_row() {
+        const STRIDE: usize = 4;
+        let mut buf = vec![0u8; STRIDE * 10];
+        let im = ImageRefMut::<Mono8>::new(3, 10, STRIDE, &mut buf).unwrap();
+        assert!(im.split_at_row(11).is_none());
+
+        let im = ImageRefMut::<Mono8>::new(3, 10, STRIDE, &mut buf).unwrap();
+        let (mut top, mut bottom) = im.split_at_row(4).unwrap();
+        assert_eq!((top.width(), top.height(), top.stride()), (3, 4, STRIDE));
+        assert_eq!((bottom.width(), bottom.height()), (3, 6));
+        for row in top.rowchunks_exact_mut() {
+            row.iter_mut().for_each(|x| *x = 1);
+        }
+        for row in bottom.rowchunks_exact_mut() {
+            row.iter_mut().for_each(|x| *x = 2);
+        }
+
+        for (row_idx, row) in buf.chunks_exact(STRIDE).enumerate() {
+            let expected = if row_idx < 4 { 1 } else { 2 };
+            assert_eq!(row, &[expected, expected, expected, 0]);
+        }
+
+        let im = ImageRefMut::<Mono8>::new(3, 10, STRIDE, &mut buf).unwrap();
+        let (top, bottom) = im.split_at_row(0).unwrap();
+        assert!(top.is_empty());
+        assert_eq!(top.rowchunks_exact().count(), 0);
+        assert_eq!(
+            crate::Validate::validate(&top),
+            Err(crate::error::ValidationError::ZeroHeight)
+        );
+        assert_eq!(bottom.height(), 10);
+
+        let im = ImageRefMut::<Mono8>::new(3, 10, STRIDE, &mut buf).unwrap();
+        let (top, bottom) = im.split_at_row(10).unwrap();
+        assert_eq!(top.height(), 10);
+        assert!(bottom.is_empty());
+        assert_eq!(bottom.rowchunks_exact().count(), 0);
+    }
+
+    #[test]
+    fn test_new_packed() {
+        let buf = [0u8; 12];
+        let im = ImageRef::<RGB8>::new_packed(2, 2, &buf).unwrap();
+        assert_eq!(im.stride(), 2 * 3);
+        assert!(ImageRef::<RGB8>::new_packed(2, 3, &buf).is_none());
+        assert!(ImageRef::<NV12>::new_packed(2, 2, &buf).is_none());
+
+        let mut buf = [0u8; 12];
+        let im = ImageRefMut::<RGB8>::new_packed(4, 1, &mut buf).unwrap();
+        assert_eq!(im.stride(), 4 * 3);
+    }
+}
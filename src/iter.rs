@@ -1,28 +1,39 @@
 //! Types to facilitate iterating over images
 
-use crate::{pixel_format, ImageMutStride, ImageStride, PixelFormat};
+use crate::{pixel_format::Mono8, ImageMutStride, ImageStride, PixelFormat};
 
 /// An image whose rows can be iterated over.
 // In a semver-breaking change, we could eliminate this trait and make its
 // method part of ImageStride.
 pub trait HasRowChunksExact<F>: ImageStride<F> {
     fn rowchunks_exact(&self) -> RowChunksExact<'_>;
+
+    /// Iterate over the rows together with their y coordinate.
+    ///
+    /// Yields `(y, row)` for `y` in `0..height` where `row` holds the valid
+    /// bytes of that row. Like [HasRowChunksExact::rowchunks_exact], iteration
+    /// stops early if the buffer is too short to hold the valid bytes of a row.
+    fn rowchunks_exact_indexed(&self) -> std::iter::Zip<std::ops::Range<u32>, RowChunksExact<'_>> {
+        (0..self.height()).zip(self.rowchunks_exact())
+    }
 }
 
 impl<S, F> HasRowChunksExact<F> for S
 where
-    S: ImageStride<F>,
+    S: ImageStride<F> + ?Sized,
     F: PixelFormat,
 {
     fn rowchunks_exact(&self) -> RowChunksExact<'_> {
-        let fmt = pixel_format::pixfmt::<F>().unwrap();
-        let valid_stride = fmt.bits_per_pixel() as usize * self.width() as usize / 8;
+        let valid_stride = crate::min_stride::<F>(self.width());
 
         let stride = self.stride();
         let height = self.height() as usize;
         let buf = self.buffer_ref().data;
-        let max_len = buf.len().min(stride * height);
-        let buf = &buf[..max_len.min(max_len)];
+        let max_len = stride
+            .checked_mul(height)
+            .map(|p| buf.len().min(p))
+            .unwrap_or(buf.len());
+        let buf = &buf[..max_len];
 
         RowChunksExact {
             buf,
@@ -50,7 +61,17 @@ impl std::fmt::Debug for RowChunksExact<'_> {
 impl<'a> Iterator for RowChunksExact<'a> {
     type Item = &'a [u8];
 
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n_rows = self.len();
+        (n_rows, Some(n_rows))
+    }
+
     fn next(&mut self) -> Option<Self::Item> {
+        // Rows of a zero-width image have no valid bytes. Yield nothing rather
+        // than an endless sequence of empty rows.
+        if self.valid_stride == 0 {
+            return None;
+        }
         if self.buf.len() >= self.valid_stride {
             let mut data: &'a [u8] = &[];
             std::mem::swap(&mut self.buf, &mut data);
@@ -67,6 +88,51 @@ impl<'a> Iterator for RowChunksExact<'a> {
     }
 }
 
+impl ExactSizeIterator for RowChunksExact<'_> {
+    fn len(&self) -> usize {
+        rows_remaining(self.buf.len(), self.stride, self.valid_stride)
+    }
+}
+
+impl<'a> DoubleEndedIterator for RowChunksExact<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let n_rows = self.len();
+        if n_rows == 0 {
+            return None;
+        }
+        let mut data: &'a [u8] = &[];
+        std::mem::swap(&mut self.buf, &mut data);
+        let (rest, last) = data.split_at((n_rows - 1) * self.stride);
+        self.buf = rest;
+        Some(&last[..self.valid_stride])
+    }
+}
+
+/// The number of complete rows in a buffer.
+///
+/// A row is complete if the buffer holds its valid bytes. The padding after
+/// the final row may be absent.
+#[inline]
+fn rows_remaining(buf_len: usize, stride: usize, valid_stride: usize) -> usize {
+    if valid_stride == 0 || buf_len < valid_stride {
+        0
+    } else {
+        (buf_len - valid_stride) / stride + 1
+    }
+}
+
+/// Iterate over the rows of two images in lockstep.
+///
+/// Yields pairs of the valid bytes of corresponding rows and stops at the end
+/// of the shorter image. This assumes the images have the same width; check
+/// with [crate::ops::same_geometry] first if needed.
+pub fn zip_rows<'a, F: PixelFormat>(
+    a: &'a dyn ImageStride<F>,
+    b: &'a dyn ImageStride<F>,
+) -> std::iter::Zip<RowChunksExact<'a>, RowChunksExact<'a>> {
+    a.rowchunks_exact().zip(b.rowchunks_exact())
+}
+
 /// An image whose mutable rows can be iterated over.
 // In a semver-breaking change, we could eliminate this trait and make its
 // method part of ImageMutStride.
@@ -75,18 +141,20 @@ pub trait HasRowChunksExactMut<F>: ImageMutStride<F> {
 }
 impl<S, F> HasRowChunksExactMut<F> for S
 where
-    S: ImageMutStride<F>,
+    S: ImageMutStride<F> + ?Sized,
     F: PixelFormat,
 {
     fn rowchunks_exact_mut(&mut self) -> RowChunksExactMut<'_> {
-        let fmt = pixel_format::pixfmt::<F>().unwrap();
-        let valid_stride = fmt.bits_per_pixel() as usize * self.width() as usize / 8;
+        let valid_stride = crate::min_stride::<F>(self.width());
 
         let stride = self.stride();
         let height = self.height() as usize;
         let buf = self.buffer_mut_ref().data;
-        let max_len = buf.len().min(stride * height);
-        let buf = &mut buf[..max_len.min(max_len)];
+        let max_len = stride
+            .checked_mul(height)
+            .map(|p| buf.len().min(p))
+            .unwrap_or(buf.len());
+        let buf = &mut buf[..max_len];
         RowChunksExactMut {
             buf,
             stride,
@@ -113,7 +181,17 @@ impl std::fmt::Debug for RowChunksExactMut<'_> {
 impl<'a> Iterator for RowChunksExactMut<'a> {
     type Item = &'a mut [u8];
 
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n_rows = self.len();
+        (n_rows, Some(n_rows))
+    }
+
     fn next(&mut self) -> Option<Self::Item> {
+        // Rows of a zero-width image have no valid bytes. Yield nothing rather
+        // than an endless sequence of empty rows.
+        if self.valid_stride == 0 {
+            return None;
+        }
         if self.buf.len() >= self.valid_stride {
             let mut data: &'a mut [u8] = &mut [];
             std::mem::swap(&mut self.buf, &mut data);
@@ -130,10 +208,170 @@ impl<'a> Iterator for RowChunksExactMut<'a> {
     }
 }
 
+impl ExactSizeIterator for RowChunksExactMut<'_> {
+    fn len(&self) -> usize {
+        rows_remaining(self.buf.len(), self.stride, self.valid_stride)
+    }
+}
+
+/// Apply `f` to each row of an image in parallel.
+///
+/// `f` is called as `f(y, row)` for `y` in `0..height` where `row` holds the
+/// valid bytes of that row, excluding any padding. Rows are processed on the
+/// `rayon` global thread pool in no particular order. As in
+/// [HasRowChunksExactMut::rowchunks_exact_mut], rows not fully contained in
+/// the buffer are skipped.
+#[cfg(feature = "parallel")]
+pub fn par_map_rows<F, G>(img: &mut dyn ImageMutStride<F>, f: G)
+where
+    F: PixelFormat,
+    G: Fn(u32, &mut [u8]) + Sync,
+{
+    use rayon::prelude::*;
+
+    let valid_stride = crate::min_stride::<F>(img.width());
+    let stride = img.stride();
+    if valid_stride == 0 || stride == 0 {
+        return;
+    }
+    let height = img.height() as usize;
+    let buf = img.buffer_mut_ref().data;
+    let max_len = buf.len().min(stride.saturating_mul(height));
+    buf[..max_len]
+        .par_chunks_mut(stride)
+        .enumerate()
+        .for_each(|(y, row)| {
+            if let Some(row) = row.get_mut(..valid_stride) {
+                f(y as u32, row);
+            }
+        });
+}
+
+/// An image whose rows can be accessed by index.
+pub trait HasRow<F>: ImageStride<F> {
+    /// The valid bytes of row `y`.
+    ///
+    /// Returns None if `y >= height` or the buffer is too short.
+    fn row(&self, y: u32) -> Option<&[u8]>;
+}
+
+impl<S, F> HasRow<F> for S
+where
+    S: ImageStride<F> + ?Sized,
+    F: PixelFormat,
+{
+    fn row(&self, y: u32) -> Option<&[u8]> {
+        if y >= self.height() {
+            return None;
+        }
+        let valid_stride = crate::min_stride::<F>(self.width());
+        let start = crate::roi_byte_offset(0, y, self.stride(), 0)?;
+        self.image_data()
+            .get(start..start.checked_add(valid_stride)?)
+    }
+}
+
+/// An image whose mutable rows can be accessed by index.
+pub trait HasRowMut<F>: ImageMutStride<F> {
+    /// The valid bytes of row `y`.
+    ///
+    /// Returns None if `y >= height` or the buffer is too short.
+    fn row_mut(&mut self, y: u32) -> Option<&mut [u8]>;
+}
+
+impl<S, F> HasRowMut<F> for S
+where
+    S: ImageMutStride<F> + ?Sized,
+    F: PixelFormat,
+{
+    fn row_mut(&mut self, y: u32) -> Option<&mut [u8]> {
+        if y >= self.height() {
+            return None;
+        }
+        let valid_stride = crate::min_stride::<F>(self.width());
+        let start = crate::roi_byte_offset(0, y, self.stride(), 0)?;
+        let end = start.checked_add(valid_stride)?;
+        self.buffer_mut_ref().data.get_mut(start..end)
+    }
+}
+
+/// A `Mono8` image whose 2x2 neighborhoods can be iterated over.
+pub trait HasWindows2x2: ImageStride<Mono8> {
+    fn windows_2x2(&self) -> Windows2x2<'_>;
+}
+
+impl<S> HasWindows2x2 for S
+where
+    S: ImageStride<Mono8> + ?Sized,
+{
+    fn windows_2x2(&self) -> Windows2x2<'_> {
+        Windows2x2 {
+            buf: self.buffer_ref().data,
+            stride: self.stride(),
+            width: self.width() as usize,
+            height: self.height() as usize,
+            x: 0,
+            y: 0,
+        }
+    }
+}
+
+/// Iterator over the 2x2 neighborhoods of a `Mono8` image.
+///
+/// Each item is `[[top_left, top_right], [bottom_left, bottom_right]]`. The
+/// neighborhoods are yielded in raster order of their top-left pixel. Windows
+/// are only yielded where all four pixels are inside the image, so the last
+/// column and last row are never the top-left pixel and an image narrower or
+/// shorter than 2 pixels yields nothing.
+pub struct Windows2x2<'a> {
+    buf: &'a [u8],
+    stride: usize,
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+}
+
+impl std::fmt::Debug for Windows2x2<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Windows2x2")
+            .field("stride", &self.stride)
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> Iterator for Windows2x2<'a> {
+    type Item = [[u8; 2]; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.width < 2 || self.y + 1 >= self.height {
+            return None;
+        }
+        let top = self.y * self.stride + self.x;
+        let bottom = top + self.stride;
+        let window = [
+            [self.buf[top], self.buf[top + 1]],
+            [self.buf[bottom], self.buf[bottom + 1]],
+        ];
+        self.x += 1;
+        if self.x + 1 >= self.width {
+            self.x = 0;
+            self.y += 1;
+        }
+        Some(window)
+    }
+}
+
 #[cfg(test)]
+// The ROI tests predate these lints.
+#[allow(clippy::useless_vec, clippy::unnecessary_cast)]
 mod test {
     use crate::{
-        iter::{HasRowChunksExact, HasRowChunksExactMut},
+        iter::{
+            zip_rows, HasRow, HasRowChunksExact, HasRowChunksExactMut, HasRowMut, HasWindows2x2,
+        },
         pixel_format::Mono8,
         ImageBuffer, ImageBufferMutRef, ImageBufferRef, ImageData, ImageMutData, Stride,
     };
@@ -396,4 +634,251 @@ mod test {
         assert_eq!(rowchunk_iter.next(), Some(&[197, 198, 199][..]));
         assert_eq!(rowchunk_iter.next(), None);
     }
+
+    #[test]
+    fn test_windows_2x2() {
+        const STRIDE: usize = 4;
+        #[rustfmt::skip]
+        let image_data = [
+            1, 2, 3, 0,
+            4, 5, 6, 0,
+            7, 8, 9,
+        ];
+        let im = RoiIm {
+            width: 3,
+            height: 3,
+            stride: STRIDE,
+            buf: &image_data,
+        };
+        let windows: Vec<_> = im.windows_2x2().collect();
+        assert_eq!(
+            windows,
+            vec![
+                [[1, 2], [4, 5]],
+                [[2, 3], [5, 6]],
+                [[4, 5], [7, 8]],
+                [[5, 6], [8, 9]],
+            ]
+        );
+
+        let narrow = RoiIm {
+            width: 1,
+            height: 3,
+            stride: STRIDE,
+            buf: &image_data,
+        };
+        assert_eq!(narrow.windows_2x2().next(), None);
+    }
+
+    #[test]
+    fn test_rowchunks_exact_indexed() {
+        // The final row is truncated and therefore not yielded.
+        let buf = [0, 0, 9, 1, 1, 9, 2];
+        let im = RoiIm {
+            width: 2,
+            height: 3,
+            stride: 3,
+            buf: &buf[..],
+        };
+        let rows: Vec<(u32, &[u8])> = im.rowchunks_exact_indexed().collect();
+        assert_eq!(rows.len(), 2);
+        for (y, row) in rows {
+            assert_eq!(row, &[y as u8, y as u8]);
+        }
+    }
+
+    #[test]
+    fn test_rowchunks_exact_rev() {
+        let buf = [1, 2, 0, 3, 4, 0, 5, 6];
+        let im = RoiIm {
+            width: 2,
+            height: 3,
+            stride: 3,
+            buf: &buf[..],
+        };
+        let rows: Vec<&[u8]> = im.rowchunks_exact().rev().collect();
+        assert_eq!(rows, vec![&[5, 6][..], &[3, 4][..], &[1, 2][..]]);
+
+        // Iterating from both ends meets in the middle.
+        let mut iter = im.rowchunks_exact();
+        assert_eq!(iter.next(), Some(&[1, 2][..]));
+        assert_eq!(iter.next_back(), Some(&[5, 6][..]));
+        assert_eq!(iter.next_back(), Some(&[3, 4][..]));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        // A truncated final row is skipped as in forward iteration.
+        let im = RoiIm {
+            width: 2,
+            height: 3,
+            stride: 3,
+            buf: &buf[..7],
+        };
+        let rows: Vec<&[u8]> = im.rowchunks_exact().rev().collect();
+        assert_eq!(rows, vec![&[3, 4][..], &[1, 2][..]]);
+    }
+
+    #[test]
+    fn test_rowchunks_exact_len() {
+        let mut buf = [1, 2, 0, 3, 4, 0, 5, 6];
+        let im = RoiIm {
+            width: 2,
+            height: 3,
+            stride: 3,
+            buf: &buf[..],
+        };
+        let mut iter = im.rowchunks_exact();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+        iter.next();
+        assert_eq!(iter.len(), 2);
+        iter.next_back();
+        assert_eq!(iter.len(), 1);
+        iter.next();
+        assert_eq!(iter.len(), 0);
+        assert_eq!(iter.next(), None);
+
+        // The truncated final row is not counted.
+        let im = RoiIm {
+            width: 2,
+            height: 3,
+            stride: 3,
+            buf: &buf[..7],
+        };
+        assert_eq!(im.rowchunks_exact().len(), 2);
+
+        let mut im = RoiImMut {
+            width: 2,
+            height: 3,
+            stride: 3,
+            buf: &mut buf[..],
+        };
+        let mut iter = im.rowchunks_exact_mut();
+        for expected in (0..3).rev() {
+            assert!(iter.next().is_some());
+            assert_eq!(iter.len(), expected);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_par_map_rows() {
+        use crate::iter::par_map_rows;
+
+        let fill = || {
+            let mut buf = vec![0u8; 100 * 7 - 1];
+            let mut im = RoiImMut {
+                width: 5,
+                height: 100,
+                stride: 7,
+                buf: &mut buf[..],
+            };
+            par_map_rows(&mut im, |y, row| {
+                for (x, value) in row.iter_mut().enumerate() {
+                    *value = (y as usize + x) as u8;
+                }
+            });
+            buf
+        };
+        let buf = fill();
+        for y in 0..100 {
+            let row = &buf[y * 7..];
+            assert_eq!(
+                &row[..5],
+                &[y as u8, y as u8 + 1, y as u8 + 2, y as u8 + 3, y as u8 + 4]
+            );
+            if y < 99 {
+                assert_eq!(&row[5..7], &[0, 0]);
+            }
+        }
+        assert_eq!(fill(), buf);
+    }
+
+    #[test]
+    fn test_rowchunks_exact_overflow() {
+        // `stride * height` overflows `usize`.
+        let mut buf = [1, 2, 3, 4];
+        let im = RoiIm {
+            width: 2,
+            height: u32::MAX,
+            stride: usize::MAX / 2,
+            buf: &buf[..],
+        };
+        let rows: Vec<&[u8]> = im.rowchunks_exact().collect();
+        assert_eq!(rows, vec![&[1, 2][..]]);
+
+        let mut im = RoiImMut {
+            width: 2,
+            height: u32::MAX,
+            stride: usize::MAX / 2,
+            buf: &mut buf[..],
+        };
+        assert_eq!(im.rowchunks_exact_mut().count(), 1);
+    }
+
+    #[test]
+    fn test_row() {
+        let mut buf = [1, 2, 0, 3, 4, 0, 5, 6];
+        let im = RoiIm {
+            width: 2,
+            height: 3,
+            stride: 3,
+            buf: &buf[..],
+        };
+        assert_eq!(im.row(0), Some(&[1, 2][..]));
+        assert_eq!(im.row(2), Some(&[5, 6][..]));
+        assert_eq!(im.row(3), None);
+
+        let mut im = RoiImMut {
+            width: 2,
+            height: 3,
+            stride: 3,
+            buf: &mut buf[..],
+        };
+        im.row_mut(2).unwrap()[0] = 50;
+        assert!(im.row_mut(3).is_none());
+        assert_eq!(buf, [1, 2, 0, 3, 4, 0, 50, 6]);
+    }
+
+    #[test]
+    fn test_zero_width() {
+        let mut buf = [0u8; 4];
+        let im = RoiIm {
+            width: 0,
+            height: 2,
+            stride: 2,
+            buf: &buf[..],
+        };
+        assert_eq!(im.rowchunks_exact().count(), 0);
+        let mut im = RoiImMut {
+            width: 0,
+            height: 2,
+            stride: 2,
+            buf: &mut buf[..],
+        };
+        assert_eq!(im.rowchunks_exact_mut().count(), 0);
+    }
+
+    #[test]
+    fn test_zip_rows() {
+        let a_buf = [1, 2, 0, 3, 4, 0, 5, 6];
+        let a = RoiIm {
+            width: 2,
+            height: 3,
+            stride: 3,
+            buf: &a_buf[..],
+        };
+        let b_buf = [10, 20, 30, 40];
+        let b = RoiIm {
+            width: 2,
+            height: 2,
+            stride: 2,
+            buf: &b_buf[..],
+        };
+        let pairs: Vec<(&[u8], &[u8])> = zip_rows(&a, &b).collect();
+        assert_eq!(
+            pairs,
+            vec![(&[1, 2][..], &[10, 20][..]), (&[3, 4][..], &[30, 40][..])]
+        );
+    }
 }
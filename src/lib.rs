@@ -11,6 +11,28 @@
 //!   layouts in the [iter] module.
 //! - Includes structs which reference image data in the [image_ref] module.
 //! - Includes struct which owns image data in the [owned] module.
+//! - Includes an image which either mutably borrows or owns its data in the
+//!   [cow] module.
+//! - Includes functions to convert between pixel formats in the [convert]
+//!   module, to operate on pixel values in the [ops] module and to transform
+//!   images geometrically in the [geometry] module.
+//! - Includes functions to compute image statistics in the [stats] module.
+//! - Includes functions to draw simple shapes in the [draw] module.
+//! - With the `ndarray` feature, images can be viewed as `ndarray` arrays
+//!   without copying using the traits in the `array_view` module.
+//! - With the `bytemuck` feature, the pixels of tightly packed images can be
+//!   accessed as typed slices using the traits in the `pixels` module.
+//! - With the `crc` feature, a CRC32 checksum of the valid pixels can be
+//!   computed using the trait in the `checksum` module.
+//! - With the `mmap` feature, large raw files can be used as images without
+//!   loading them into memory using `owned::MmapImage`.
+//! - With the `wgpu-helpers` feature, the layout and texture format for
+//!   uploading images to the GPU with `wgpu` can be computed using the trait
+//!   in the `wgpu_helpers` module.
+//! - With the `simd` feature, some conversions such as
+//!   [convert::rgb8_to_mono8] process multiple pixels per iteration.
+//! - With the `parallel` feature, rows can be processed on a thread pool using
+//!   `iter::par_map_rows`.
 //!
 //! Additionally several traits are defined to describe image data:
 //!
@@ -18,16 +40,18 @@
 //!   buffer.
 //! - [ImageMutData] is implemented for images with mutable data.
 //! - [Stride] is implemented for images with strided data (i.e. each image row
-//!    is encoded with exactly the same number of bytes, which may including
-//!    padding).
+//!   is encoded with exactly the same number of bytes, which may including
+//!   padding).
 //! - Compound traits combine these basic traits. [ImageStride] implements both
 //!   [ImageData] and [Stride]. [ImageMutStride] implements [ImageMutData] and
 //!   [Stride]. [OwnedImage] implements [AsImageData], [ImageData], and
 //!   [Into<Vec<`u8`>>]. [OwnedImageStride] implements [AsImageStride],
-//!   [ImageStride], and [Into<Vec<`u8`>>].
+//!   [ImageStride], and [Into<Vec<`u8`>>]. [CloneableOwnedImage] implements
+//!   [OwnedImage] and `Clone`.
 //! - Converter traits: [AsImageData] allows converting to `&dyn ImageData`,
 //!   [AsImageStride] to `&dyn ImageStride`, and [AsImageMutStride] to `&dyn
 //!   ImageMutStride`.
+//! - [Validate] checks that the buffer of an image matches its geometry.
 //!
 //! This crate is used extensively in [Strand
 //! Camera](https://github.com/strawlab/strand-braid).
@@ -40,14 +64,37 @@ extern crate core as std;
 extern crate alloc;
 
 #[cfg(feature = "alloc")]
-use alloc::vec::Vec;
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
 
+#[cfg(feature = "ndarray")]
+pub mod array_view;
+#[cfg(feature = "crc")]
+pub mod checksum;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod convert;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod cow;
+pub mod draw;
+pub mod error;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod geometry;
 pub mod image_ref;
 pub mod iter;
+pub mod ops;
 #[cfg(any(feature = "std", feature = "alloc"))]
 pub mod owned;
 #[allow(non_camel_case_types)]
 pub mod pixel_format;
+#[cfg(feature = "bytemuck")]
+pub mod pixels;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod stats;
+#[cfg(feature = "wgpu-helpers")]
+pub mod wgpu_helpers;
 
 // re-export
 pub use pixel_format::{PixFmt, PixelFormat};
@@ -159,6 +206,85 @@ impl<F> ImageBuffer<F> {
     }
 }
 
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<F> std::ops::Deref for ImageBuffer<F> {
+    type Target = [u8];
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<F> std::ops::DerefMut for ImageBuffer<F> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+}
+
+#[test]
+fn test_image_buffer_deref() {
+    fn sum(bytes: &[u8]) -> u32 {
+        bytes.iter().map(|x| *x as u32).sum()
+    }
+    let mut buf = ImageBuffer::<pixel_format::Mono8>::new(vec![1, 2, 3]);
+    assert_eq!(sum(&buf), 6);
+    buf[0] = 10;
+    assert_eq!(buf.len(), 3);
+    assert_eq!(buf.data, vec![10, 2, 3]);
+}
+
+// ------------------------------- helpers ----------------------
+
+/// The byte offset of pixel `(x, y)` in a buffer with the given stride and
+/// bytes per pixel.
+///
+/// Returns None if the computation overflows.
+#[inline]
+pub(crate) fn roi_byte_offset(
+    x: u32,
+    y: u32,
+    stride: usize,
+    bytes_per_pixel: usize,
+) -> Option<usize> {
+    let row_offset = (y as usize).checked_mul(stride)?;
+    let col_offset = (x as usize).checked_mul(bytes_per_pixel)?;
+    row_offset.checked_add(col_offset)
+}
+
+#[test]
+fn test_roi_byte_offset() {
+    assert_eq!(roi_byte_offset(0, 0, 10, 3), Some(0));
+    assert_eq!(roi_byte_offset(2, 3, 10, 3), Some(36));
+    assert_eq!(roi_byte_offset(0, 2, usize::MAX - 1, 1), None);
+    assert_eq!(roi_byte_offset(1, 1, usize::MAX, 1), None);
+    assert_eq!(roi_byte_offset(0, 1, usize::MAX, 1), Some(usize::MAX));
+}
+
+/// Write a one line summary such as `Mono8 640x480 stride=640`.
+///
+/// Pixel formats from outside this crate are shown as `unknown`.
+pub(crate) fn fmt_summary<F: PixelFormat>(
+    f: &mut std::fmt::Formatter,
+    width: u32,
+    height: u32,
+    stride: usize,
+) -> std::fmt::Result {
+    let name = pixel_format::pixfmt::<F>()
+        .map(|fmt| fmt.as_str())
+        .unwrap_or("unknown");
+    write!(f, "{} {}x{} stride={}", name, width, height, stride)
+}
+
+/// The number of bytes holding the valid pixels of a row `width` pixels wide.
+///
+/// This rounds up for formats whose rows do not end on a byte boundary.
+#[inline]
+pub(crate) fn min_stride<F: PixelFormat>(width: u32) -> usize {
+    (F::BITS_PER_PIXEL as usize * width as usize + 7) / 8
+}
+
 // ------------------------------- simple traits ----------------------
 
 /// An image.
@@ -191,9 +317,76 @@ pub trait ImageData<F> {
     /// copy, use the `Into<Vec<u8>>` trait required by the OwnedImage trait.
     #[cfg(any(feature = "std", feature = "alloc"))]
     fn buffer(self) -> ImageBuffer<F>;
-    // fn pixel_format(&self) -> std::marker::PhantomData<F> {
-    //     std::marker::PhantomData
-    // }
+    /// Returns the pixel format `F` as a runtime value.
+    ///
+    /// Returns None if `F` is not one of the pixel formats defined in this
+    /// crate.
+    #[inline]
+    fn try_pixel_format(&self) -> Option<PixFmt>
+    where
+        F: PixelFormat,
+    {
+        pixel_format::pixfmt::<F>().ok()
+    }
+    /// Returns the pixel format `F` as a runtime value.
+    ///
+    /// Panics if `F` is not one of the pixel formats defined in this crate. Use
+    /// `try_pixel_format()` to handle this case.
+    #[inline]
+    fn pixel_format(&self) -> PixFmt
+    where
+        F: PixelFormat,
+    {
+        self.try_pixel_format()
+            .expect("pixel format not defined in machine-vision-formats")
+    }
+    /// The number of pixels, `width * height`.
+    ///
+    /// This is computed as `u64` and so cannot overflow.
+    #[inline]
+    fn pixel_count(&self) -> u64 {
+        self.width() as u64 * self.height() as u64
+    }
+    /// Whether the image has no pixels because its width or height is zero.
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.width() == 0 || self.height() == 0
+    }
+}
+
+#[test]
+fn test_pixel_count() {
+    use crate::{image_ref::ImageRef, pixel_format::RGB8};
+
+    // Dimensions only, for sizes no real buffer could back.
+    struct Dims(u32, u32);
+    impl ImageData<RGB8> for Dims {
+        fn width(&self) -> u32 {
+            self.0
+        }
+        fn height(&self) -> u32 {
+            self.1
+        }
+        fn buffer_ref(&self) -> ImageBufferRef<'_, RGB8> {
+            ImageBufferRef::new(&[])
+        }
+        #[cfg(any(feature = "std", feature = "alloc"))]
+        fn buffer(self) -> ImageBuffer<RGB8> {
+            ImageBuffer::new(Vec::new())
+        }
+    }
+
+    let buf = [0u8; 12];
+    let im = ImageRef::<RGB8>::new(2, 2, 6, &buf).unwrap();
+    assert_eq!(im.pixel_count(), 4);
+    assert!(!im.is_empty());
+
+    let zero_height = Dims(2, 0);
+    assert_eq!(zero_height.pixel_count(), 0);
+    assert!(zero_height.is_empty());
+
+    let huge = Dims(u32::MAX, u32::MAX);
+    assert_eq!(huge.pixel_count(), u32::MAX as u64 * u32::MAX as u64);
 }
 
 /// A mutable image.
@@ -241,8 +434,95 @@ where
 {
 }
 
+#[cfg(any(feature = "std", feature = "alloc"))]
+/// An owned image which can be cloned.
+///
+/// This adds `Clone` as a supertrait of [OwnedImage] without a breaking
+/// change. Because `Clone` prevents use as a trait object,
+/// [CloneableOwnedImage::boxed_clone] clones into a `Box<dyn ImageData<F>>`.
+pub trait CloneableOwnedImage<F>: OwnedImage<F> + Clone {
+    /// Clone into a boxed trait object.
+    fn boxed_clone(&self) -> Box<dyn ImageData<F>>
+    where
+        Self: 'static;
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<S, F> CloneableOwnedImage<F> for S
+where
+    S: OwnedImage<F> + Clone,
+{
+    fn boxed_clone(&self) -> Box<dyn ImageData<F>>
+    where
+        Self: 'static,
+    {
+        Box::new(self.clone())
+    }
+}
+
 /// An image with a stride.
-pub trait ImageStride<F>: ImageData<F> + Stride {}
+pub trait ImageStride<F>: ImageData<F> + Stride {
+    /// The byte range of the valid data of row `y` within the image buffer.
+    ///
+    /// The range starts at `y * stride` and excludes any padding at the end of
+    /// the row. Returns None if `y` is not a row of the image or the range
+    /// does not fit in `usize`.
+    fn row_byte_range(&self, y: u32) -> Option<core::ops::Range<usize>>
+    where
+        F: PixelFormat,
+    {
+        if y >= self.height() {
+            return None;
+        }
+        let start = roi_byte_offset(0, y, self.stride(), 0)?;
+        let end = start.checked_add(min_stride::<F>(self.width()))?;
+        Some(start..end)
+    }
+
+    /// Copy into a newly allocated, boxed [owned::OImage].
+    ///
+    /// This allows images of different concrete types to be stored together,
+    /// for example as `Vec<Box<dyn BoxedImageStride<F>>>`. The copy has the
+    /// same dimensions and stride.
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn to_owned_boxed(&self) -> Box<dyn BoxedImageStride<F>>
+    where
+        F: PixelFormat,
+    {
+        Box::new(owned::OImage::copy_from(self))
+    }
+
+    /// The number of padding bytes after the valid bytes of each row, summed
+    /// over all rows.
+    ///
+    /// This is `(stride - valid_stride) * height`, where `valid_stride` is the
+    /// number of valid bytes in a row. A tightly packed image has no padding.
+    fn padding_bytes(&self) -> usize
+    where
+        F: PixelFormat,
+    {
+        let valid_stride = min_stride::<F>(self.width());
+        self.stride()
+            .saturating_sub(valid_stride)
+            .saturating_mul(self.height() as usize)
+    }
+
+    /// The fraction of `stride * height` bytes which are padding.
+    ///
+    /// Returns 0 for an image with no rows or a zero stride. A high value
+    /// suggests copying into a tightly packed image, e.g. with
+    /// `OImage::pad_to_stride`, to save memory.
+    fn padding_fraction(&self) -> f32
+    where
+        F: PixelFormat,
+    {
+        let total = self.stride() as f64 * self.height() as f64;
+        if total == 0.0 {
+            return 0.0;
+        }
+        (self.padding_bytes() as f64 / total) as f32
+    }
+}
 
 impl<S, F> ImageStride<F> for S where S: ImageData<F> + Stride {}
 
@@ -257,9 +537,114 @@ impl<S: ImageStride<F>, F> AsImageStride<F> for S {
 }
 
 /// A mutable image with a stride.
-pub trait ImageMutStride<F>: ImageMutData<F> + Stride {}
+pub trait ImageMutStride<F>: ImageMutData<F> + Stride {
+    /// Split into the valid bytes of each row, collected into a `Vec`.
+    ///
+    /// Unlike [iter::HasRowChunksExactMut::rowchunks_exact_mut], all rows are
+    /// available at once and can be accessed by index, in any order. Row
+    /// padding is excluded.
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn rows_mut_vec(&mut self) -> Vec<&mut [u8]>
+    where
+        F: PixelFormat,
+    {
+        use crate::iter::HasRowChunksExactMut;
+        self.rowchunks_exact_mut().collect()
+    }
+    /// Reverse the order of the pixels within each row, leaving row padding
+    /// untouched.
+    ///
+    /// Panics if pixels of the format do not occupy a whole number of bytes.
+    fn mirror_horizontal_in_place(&mut self)
+    where
+        F: PixelFormat,
+    {
+        use crate::iter::HasRowChunksExactMut;
+        assert!(
+            F::BITS_PER_PIXEL % 8 == 0,
+            "pixels must occupy a whole number of bytes"
+        );
+        let bytes_per_pixel = F::BITS_PER_PIXEL as usize / 8;
+        for row in self.rowchunks_exact_mut() {
+            // Reversing the bytes of the row reverses the pixel order but also
+            // the bytes within each pixel, so restore those afterwards.
+            row.reverse();
+            for pixel in row.chunks_exact_mut(bytes_per_pixel) {
+                pixel.reverse();
+            }
+        }
+    }
+    /// Set all valid pixels to zero, leaving row padding untouched.
+    fn clear(&mut self)
+    where
+        F: PixelFormat,
+    {
+        use crate::iter::HasRowChunksExactMut;
+        for row in self.rowchunks_exact_mut() {
+            row.fill(0);
+        }
+    }
+    /// Set the whole buffer, including row padding, to zero.
+    fn clear_all(&mut self) {
+        self.buffer_mut_ref().data.fill(0);
+    }
+}
 impl<S, F> ImageMutStride<F> for S where S: ImageMutData<F> + Stride {}
 
+#[test]
+fn test_rows_mut_vec() {
+    use crate::{image_ref::ImageRefMut, pixel_format::Mono8};
+
+    let mut buf = [1, 2, 0, 3, 4, 0, 5, 6];
+    let mut im = ImageRefMut::<Mono8>::new(2, 3, 3, &mut buf).unwrap();
+    let mut rows = im.rows_mut_vec();
+    assert_eq!(rows.len(), 3);
+    rows[2][0] = 50;
+    rows[0][1] = 20;
+    rows.swap(0, 1);
+    rows[0][0] = 30;
+    assert_eq!(buf, [1, 20, 0, 30, 4, 0, 50, 6]);
+}
+
+#[test]
+fn test_mirror_horizontal_in_place() {
+    use crate::{
+        image_ref::ImageRefMut,
+        pixel_format::{Mono8, RGB8},
+    };
+
+    let mut buf = [1, 2, 3, 4, 5, 6, 7, 8, 9, 0];
+    {
+        let mut im = ImageRefMut::<RGB8>::new(3, 1, 10, &mut buf).unwrap();
+        im.mirror_horizontal_in_place();
+    }
+    assert_eq!(buf, [7, 8, 9, 4, 5, 6, 1, 2, 3, 0]);
+
+    let mut buf = [1, 2, 3, 4, 9, 5, 6, 7, 8];
+    {
+        let mut im = ImageRefMut::<Mono8>::new(4, 2, 5, &mut buf).unwrap();
+        im.mirror_horizontal_in_place();
+    }
+    assert_eq!(buf, [4, 3, 2, 1, 9, 8, 7, 6, 5]);
+}
+
+#[test]
+fn test_clear() {
+    use crate::{image_ref::ImageRefMut, pixel_format::Mono8};
+
+    let mut buf = [1, 2, 9, 3, 4];
+    {
+        let mut im = ImageRefMut::<Mono8>::new(2, 2, 3, &mut buf).unwrap();
+        im.clear();
+    }
+    assert_eq!(buf, [0, 0, 9, 0, 0]);
+    {
+        let mut im = ImageRefMut::<Mono8>::new(2, 2, 3, &mut buf).unwrap();
+        im.clear_all();
+    }
+    assert_eq!(buf, [0; 5]);
+}
+
 /// Can be converted into `ImageMutStride`.
 pub trait AsImageMutStride<F>: ImageMutStride<F> {
     fn as_image_mut_stride(&self) -> &dyn ImageMutStride<F>;
@@ -270,6 +655,219 @@ impl<S: ImageMutStride<F>, F> AsImageMutStride<F> for S {
     }
 }
 
+/// An image whose geometry can be checked against its buffer.
+pub trait Validate<F>: ImageStride<F> {
+    /// Check that the buffer can hold an image of the reported dimensions
+    /// and stride.
+    ///
+    /// The checks are, in order: the height is not zero, the stride holds the
+    /// valid bytes of a row, the required buffer size does not overflow and
+    /// the buffer is at least that large.
+    fn validate(&self) -> Result<(), error::ValidationError>;
+}
+
+impl<S, F> Validate<F> for S
+where
+    S: ImageStride<F> + ?Sized,
+    F: PixelFormat,
+{
+    fn validate(&self) -> Result<(), error::ValidationError> {
+        use error::ValidationError;
+        let height = self.height();
+        if height == 0 {
+            return Err(ValidationError::ZeroHeight);
+        }
+        let min_stride = (F::BITS_PER_PIXEL as usize)
+            .checked_mul(self.width() as usize)
+            .and_then(|bits| bits.checked_add(7))
+            .ok_or(ValidationError::Overflow)?
+            / 8;
+        let stride = self.stride();
+        if stride < min_stride {
+            return Err(ValidationError::StrideTooSmall { stride, min_stride });
+        }
+        let required = stride
+            .checked_mul(height as usize - 1)
+            .and_then(|x| x.checked_add(min_stride))
+            .ok_or(ValidationError::Overflow)?;
+        let actual = self.image_data().len();
+        if actual < required {
+            return Err(ValidationError::BufferTooSmall { required, actual });
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_validate() {
+    use crate::{error::ValidationError, owned::OImage, pixel_format::RGB8};
+
+    let ok = OImage::<RGB8>::new(2, 2, 6, vec![0; 12]).unwrap();
+    assert_eq!(ok.validate(), Ok(()));
+
+    // SAFETY: the images are only used to exercise `validate`.
+    let zero_height = unsafe { OImage::<RGB8>::new_unchecked(2, 0, 6, vec![]) };
+    assert_eq!(zero_height.validate(), Err(ValidationError::ZeroHeight));
+
+    let small_stride = unsafe { OImage::<RGB8>::new_unchecked(2, 2, 5, vec![0; 12]) };
+    assert_eq!(
+        small_stride.validate(),
+        Err(ValidationError::StrideTooSmall {
+            stride: 5,
+            min_stride: 6
+        })
+    );
+
+    let overflow = unsafe { OImage::<RGB8>::new_unchecked(2, 3, usize::MAX, vec![0; 12]) };
+    assert_eq!(overflow.validate(), Err(ValidationError::Overflow));
+
+    let short = unsafe { OImage::<RGB8>::new_unchecked(2, 2, 8, vec![0; 12]) };
+    assert_eq!(
+        short.validate(),
+        Err(ValidationError::BufferTooSmall {
+            required: 14,
+            actual: 12
+        })
+    );
+}
+
+#[test]
+fn test_row_byte_range() {
+    use crate::{image_ref::ImageRef, pixel_format::RGB8};
+
+    let buf = [0u8; 22];
+    let im = ImageRef::<RGB8>::new(2, 3, 8, &buf).unwrap();
+    assert_eq!(im.row_byte_range(0), Some(0..6));
+    assert_eq!(im.row_byte_range(2), Some(16..22));
+    assert_eq!(im.row_byte_range(3), None);
+    assert_eq!(im.row_byte_range(u32::MAX), None);
+    let last = im.row_byte_range(2).unwrap();
+    assert_eq!(&im.image_data()[last], &buf[16..22]);
+}
+
+#[test]
+fn test_padding_bytes() {
+    use crate::{image_ref::ImageRef, pixel_format::RGB8};
+
+    let buf = [0u8; 32];
+    let im = ImageRef::<RGB8>::new(2, 4, 8, &buf).unwrap();
+    assert_eq!(im.padding_bytes(), 8);
+    assert_eq!(im.padding_fraction(), 0.25);
+
+    let packed = ImageRef::<RGB8>::new(2, 4, 6, &buf).unwrap();
+    assert_eq!(packed.padding_bytes(), 0);
+    assert_eq!(packed.padding_fraction(), 0.0);
+}
+
+#[test]
+fn test_to_owned_boxed() {
+    use crate::{image_ref::ImageRef, pixel_format::Mono8};
+
+    let buf = [1, 2, 0, 3, 4, 0];
+    let im = ImageRef::<Mono8>::new(2, 2, 3, &buf).unwrap();
+    let boxed: Vec<Box<dyn BoxedImageStride<Mono8>>> =
+        vec![im.to_owned_boxed(), im.to_owned_boxed()];
+    for b in boxed.iter() {
+        assert_eq!((b.width(), b.height(), b.stride()), (2, 2, 3));
+        assert_eq!(b.image_data(), &buf[..]);
+    }
+    for b in boxed {
+        assert_eq!(b.into_vec(), buf.to_vec());
+    }
+}
+
+/// Format the valid pixels of an image as a text grid for debugging.
+///
+/// Each row of the image is one line. Pixels are separated by spaces and the
+/// channels of a pixel by commas, with all values right-aligned to the same
+/// width. Formats without one sample per channel, such as subsampled YUV
+/// formats, are shown as hexadecimal bytes instead. Row padding is omitted.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn debug_dump<F: PixelFormat>(img: &dyn ImageStride<F>) -> String {
+    use crate::{iter::HasRowChunksExact, pixel_format::SampleType};
+    use std::fmt::Write;
+
+    let layout = pixel_format::pixfmt::<F>().ok().and_then(|fmt| {
+        let sample_size = match fmt.sample_type() {
+            SampleType::U8 => 1,
+            SampleType::U16 => 2,
+            SampleType::F32 => 4,
+        };
+        let channels = fmt.channels() as usize;
+        if fmt.bits_per_pixel() as usize == channels * sample_size * 8 {
+            Some((fmt, channels * sample_size, sample_size))
+        } else {
+            None
+        }
+    });
+    let format_sample = |fmt: PixFmt, b: &[u8]| match b.len() {
+        1 => b[0].to_string(),
+        2 if fmt == PixFmt::Mono16Be => u16::from_be_bytes([b[0], b[1]]).to_string(),
+        2 => u16::from_le_bytes([b[0], b[1]]).to_string(),
+        _ => f32::from_le_bytes([b[0], b[1], b[2], b[3]]).to_string(),
+    };
+
+    let mut cells: Vec<Vec<String>> = Vec::new();
+    for row in img.rowchunks_exact() {
+        let row_cells = match layout {
+            Some((fmt, pixel_size, sample_size)) => row
+                .chunks_exact(pixel_size)
+                .map(|pixel| {
+                    let samples: Vec<String> = pixel
+                        .chunks_exact(sample_size)
+                        .map(|b| format_sample(fmt, b))
+                        .collect();
+                    samples.join(",")
+                })
+                .collect(),
+            None => row
+                .iter()
+                .map(|b| {
+                    let mut cell = String::new();
+                    let _ = write!(cell, "{:02x}", b);
+                    cell
+                })
+                .collect(),
+        };
+        cells.push(row_cells);
+    }
+    let width = cells.iter().flatten().map(|c| c.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for row in cells.iter() {
+        let row: Vec<String> = row
+            .iter()
+            .map(|c| {
+                let mut cell = String::new();
+                let _ = write!(cell, "{:>width$}", c, width = width);
+                cell
+            })
+            .collect();
+        out.push_str(&row.join(" "));
+        out.push('\n');
+    }
+    out
+}
+
+#[test]
+fn test_debug_dump() {
+    use crate::{
+        image_ref::ImageRef,
+        pixel_format::{Mono16, Mono8, RGB8, YUV422},
+    };
+
+    let im = ImageRef::<Mono8>::new(2, 2, 3, &[1, 200, 99, 30, 4]).unwrap();
+    assert_eq!(debug_dump(&im), "  1 200\n 30   4\n");
+
+    let im = ImageRef::<RGB8>::new(2, 1, 6, &[1, 2, 3, 40, 50, 60]).unwrap();
+    assert_eq!(debug_dump(&im), "   1,2,3 40,50,60\n");
+
+    let im = ImageRef::<Mono16>::new(1, 1, 2, &[0x34, 0x12]).unwrap();
+    assert_eq!(debug_dump(&im), "4660\n");
+
+    let im = ImageRef::<YUV422>::new(2, 1, 4, &[0, 1, 0xab, 255]).unwrap();
+    assert_eq!(debug_dump(&im), "00 01 ab ff\n");
+}
+
 #[cfg(any(feature = "std", feature = "alloc"))]
 /// An image with a stride which can be moved into `Vec<u8>`.
 // TODO: any breaking release should add Clone as a supertrait
@@ -281,3 +879,22 @@ where
     Vec<u8>: From<S>,
 {
 }
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+/// A boxed image with a stride which can be moved into `Vec<u8>`.
+///
+/// Unlike [OwnedImageStride], this can be used as a trait object.
+pub trait BoxedImageStride<F>: ImageStride<F> {
+    /// Move the image buffer out of the box.
+    fn into_vec(self: Box<Self>) -> Vec<u8>;
+}
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<S, F> BoxedImageStride<F> for S
+where
+    S: ImageStride<F>,
+    Vec<u8>: From<S>,
+{
+    fn into_vec(self: Box<Self>) -> Vec<u8> {
+        (*self).into()
+    }
+}
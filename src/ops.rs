@@ -0,0 +1,547 @@
+//! Operations on pixel values
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::vec::Vec;
+
+use crate::{
+    error::BlendError,
+    iter::{HasRowChunksExact, HasRowChunksExactMut},
+    pixel_format::{Mono8, RGB8, RGBA8},
+    ImageData, ImageMutStride, ImageStride,
+};
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+use crate::{
+    error::DimMismatch, iter::zip_rows, owned::OImage, pixel_format::Mono16,
+    stats::histogram_mono8, PixelFormat,
+};
+
+/// Whether two images have the same width and height.
+///
+/// The images may have different pixel formats.
+pub fn same_geometry<A, B, SA, SB>(a: &SA, b: &SB) -> bool
+where
+    SA: ImageData<A> + ?Sized,
+    SB: ImageData<B> + ?Sized,
+{
+    a.width() == b.width() && a.height() == b.height()
+}
+
+/// Composite the `fg` image over the `bg` image using the alpha channel of
+/// `fg`.
+///
+/// Each output channel is `(fg*a + bg*(255-a))/255` computed with integer
+/// math. The images must have the same dimensions.
+pub fn blend_rgba_over_rgb(
+    fg: &dyn ImageStride<RGBA8>,
+    bg: &mut dyn ImageMutStride<RGB8>,
+) -> Result<(), BlendError> {
+    if !same_geometry(fg, &*bg) {
+        return Err(BlendError::DimensionMismatch);
+    }
+    for (fg_row, bg_row) in fg.rowchunks_exact().zip(bg.rowchunks_exact_mut()) {
+        for (fg_px, bg_px) in fg_row.chunks_exact(4).zip(bg_row.chunks_exact_mut(3)) {
+            let alpha = fg_px[3] as u16;
+            for (b, f) in bg_px.iter_mut().zip(fg_px.iter()) {
+                *b = ((*f as u16 * alpha + *b as u16 * (255 - alpha)) / 255) as u8;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Find the first nonzero pixel in raster order.
+///
+/// Returns the `(x, y)` coordinates of the pixel or None if all pixels are
+/// zero.
+pub fn first_nonzero_mono8(src: &dyn ImageStride<Mono8>) -> Option<(u32, u32)> {
+    for (y, row) in src.rowchunks_exact_indexed() {
+        if let Some(x) = row.iter().position(|value| *value != 0) {
+            return Some((x as u32, y));
+        }
+    }
+    None
+}
+
+/// Apply `op` to each valid byte of an image.
+#[cfg(any(feature = "std", feature = "alloc"))]
+fn map_u8<F: PixelFormat>(src: &dyn ImageStride<F>, op: impl Fn(u8) -> u8) -> OImage<F> {
+    let stride = crate::min_stride::<F>(src.width());
+    let mut buf = Vec::with_capacity(stride * src.height() as usize);
+    for row in src.rowchunks_exact() {
+        buf.extend(row.iter().map(|x| op(*x)));
+    }
+    OImage::from_parts(src.width(), src.height(), stride, buf)
+}
+
+/// Combine the valid pixels of two images of the same dimensions byte by byte.
+#[cfg(any(feature = "std", feature = "alloc"))]
+fn combine_u8<F: PixelFormat>(
+    a: &dyn ImageStride<F>,
+    b: &dyn ImageStride<F>,
+    op: impl Fn(u8, u8) -> u8,
+) -> Result<OImage<F>, DimMismatch> {
+    if !same_geometry(a, b) {
+        return Err(DimMismatch);
+    }
+    let stride = crate::min_stride::<F>(a.width());
+    let mut buf = Vec::with_capacity(stride * a.height() as usize);
+    for (a_row, b_row) in zip_rows(a, b) {
+        buf.extend(a_row.iter().zip(b_row.iter()).map(|(x, y)| op(*x, *y)));
+    }
+    Ok(OImage::from_parts(a.width(), a.height(), stride, buf))
+}
+
+/// Combine the valid pixels of two images of the same dimensions as
+/// little-endian `u16` samples.
+#[cfg(any(feature = "std", feature = "alloc"))]
+fn combine_u16<F: PixelFormat>(
+    a: &dyn ImageStride<F>,
+    b: &dyn ImageStride<F>,
+    op: impl Fn(u16, u16) -> u16,
+) -> Result<OImage<F>, DimMismatch> {
+    if !same_geometry(a, b) {
+        return Err(DimMismatch);
+    }
+    let stride = crate::min_stride::<F>(a.width());
+    let mut buf = Vec::with_capacity(stride * a.height() as usize);
+    for (a_row, b_row) in zip_rows(a, b) {
+        for (x, y) in a_row.chunks_exact(2).zip(b_row.chunks_exact(2)) {
+            let x = u16::from_le_bytes([x[0], x[1]]);
+            let y = u16::from_le_bytes([y[0], y[1]]);
+            buf.extend_from_slice(&op(x, y).to_le_bytes());
+        }
+    }
+    Ok(OImage::from_parts(a.width(), a.height(), stride, buf))
+}
+
+/// Add two images, clamping at 255.
+///
+/// The images must have the same dimensions. The output is tightly packed.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn add_saturating(
+    a: &dyn ImageStride<Mono8>,
+    b: &dyn ImageStride<Mono8>,
+) -> Result<OImage<Mono8>, DimMismatch> {
+    combine_u8(a, b, u8::saturating_add)
+}
+
+/// Subtract image `b` from image `a`, clamping at 0.
+///
+/// The images must have the same dimensions. The output is tightly packed.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn subtract_saturating(
+    a: &dyn ImageStride<Mono8>,
+    b: &dyn ImageStride<Mono8>,
+) -> Result<OImage<Mono8>, DimMismatch> {
+    combine_u8(a, b, u8::saturating_sub)
+}
+
+/// Add two images, clamping at 65535.
+///
+/// The images must have the same dimensions. The output is tightly packed.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn add_saturating_mono16(
+    a: &dyn ImageStride<Mono16>,
+    b: &dyn ImageStride<Mono16>,
+) -> Result<OImage<Mono16>, DimMismatch> {
+    combine_u16(a, b, u16::saturating_add)
+}
+
+/// Subtract image `b` from image `a`, clamping at 0.
+///
+/// The images must have the same dimensions. The output is tightly packed.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn subtract_saturating_mono16(
+    a: &dyn ImageStride<Mono16>,
+    b: &dyn ImageStride<Mono16>,
+) -> Result<OImage<Mono16>, DimMismatch> {
+    combine_u16(a, b, u16::saturating_sub)
+}
+
+/// Map each pixel through a lookup table, `out = lut[in]`.
+///
+/// The output is tightly packed.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn apply_lut_mono8(src: &dyn ImageStride<Mono8>, lut: &[u8; 256]) -> OImage<Mono8> {
+    map_u8(src, |x| lut[x as usize])
+}
+
+/// Map each channel of each pixel through the same lookup table.
+///
+/// The output is tightly packed.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn apply_lut_rgb8(src: &dyn ImageStride<RGB8>, lut: &[u8; 256]) -> OImage<RGB8> {
+    map_u8(src, |x| lut[x as usize])
+}
+
+/// Build a lookup table applying gamma correction.
+///
+/// Entry `i` is `255 * (i / 255)^(1 / gamma)`, rounded to the nearest
+/// integer, so a `gamma` above 1 brightens midtones and a `gamma` of 1 is the
+/// identity.
+#[cfg(feature = "std")]
+pub fn gamma_lut(gamma: f32) -> [u8; 256] {
+    let mut lut = [0u8; 256];
+    for (i, out) in lut.iter_mut().enumerate() {
+        let value = 255.0 * (i as f32 / 255.0).powf(1.0 / gamma);
+        *out = value.round().clamp(0.0, 255.0) as u8;
+    }
+    lut
+}
+
+/// Stretch the contrast of an image to the full range.
+///
+/// The `low_pct` and `high_pct` percentiles (from 0 to 100) of the pixel
+/// values are found from the histogram and pixels are remapped linearly so
+/// that the low percentile becomes 0 and the high percentile 255. Values
+/// outside that range are clamped. If the two percentiles are equal, the
+/// pixels are copied unchanged. The output is tightly packed.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn auto_contrast_mono8(
+    src: &dyn ImageStride<Mono8>,
+    low_pct: f32,
+    high_pct: f32,
+) -> OImage<Mono8> {
+    let hist = histogram_mono8(src);
+    let n_pixels: u64 = hist.iter().sum();
+    let percentile = |pct: f32| {
+        let target = ((pct.clamp(0.0, 100.0) as f64 / 100.0) * n_pixels as f64) as u64;
+        let target = target.max(1);
+        let mut cumulative = 0;
+        for (value, count) in hist.iter().enumerate() {
+            cumulative += count;
+            if cumulative >= target {
+                return value as i32;
+            }
+        }
+        255
+    };
+    let low = percentile(low_pct);
+    let high = percentile(high_pct);
+    if high <= low {
+        return map_u8(src, |x| x);
+    }
+    let range = high - low;
+    let mut lut = [0u8; 256];
+    for (i, out) in lut.iter_mut().enumerate() {
+        let value = (i as i32 - low).clamp(0, range);
+        *out = ((value * 255 + range / 2) / range) as u8;
+    }
+    apply_lut_mono8(src, &lut)
+}
+
+/// How a pixel value is compared with a threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// `value > threshold`
+    Greater,
+    /// `value >= threshold`
+    GreaterOrEqual,
+    /// `value < threshold`
+    Less,
+    /// `value <= threshold`
+    LessOrEqual,
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Comparison {
+    #[inline]
+    fn matches(self, value: u8, threshold: u8) -> bool {
+        match self {
+            Comparison::Greater => value > threshold,
+            Comparison::GreaterOrEqual => value >= threshold,
+            Comparison::Less => value < threshold,
+            Comparison::LessOrEqual => value <= threshold,
+        }
+    }
+}
+
+/// Threshold an image into a binary mask.
+///
+/// Pixels greater than `thresh` are set to `above` and all others to `below`.
+/// The output is tightly packed.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn threshold_mono8(
+    src: &dyn ImageStride<Mono8>,
+    thresh: u8,
+    above: u8,
+    below: u8,
+) -> OImage<Mono8> {
+    threshold_mono8_cmp(src, thresh, Comparison::Greater, above, below)
+}
+
+/// Threshold an image into a binary mask with a given comparison.
+///
+/// Pixels for which `value <cmp> thresh` holds are set to `matched` and all
+/// others to `unmatched`. The output is tightly packed.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn threshold_mono8_cmp(
+    src: &dyn ImageStride<Mono8>,
+    thresh: u8,
+    cmp: Comparison,
+    matched: u8,
+    unmatched: u8,
+) -> OImage<Mono8> {
+    map_u8(src, |x| {
+        if cmp.matches(x, thresh) {
+            matched
+        } else {
+            unmatched
+        }
+    })
+}
+
+/// How pixels outside the image are treated by neighborhood operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Border {
+    /// Pixels outside the image are background (zero).
+    Background,
+    /// Pixels outside the image are foreground (nonzero).
+    Foreground,
+}
+
+/// Apply a 3x3 binary morphological operation.
+///
+/// The output pixel is 255 if `op` applied to the foreground flags of the
+/// 3x3 neighborhood, in raster order, is true and 0 otherwise.
+#[cfg(any(feature = "std", feature = "alloc"))]
+fn morph3x3_mono8(
+    src: &dyn ImageStride<Mono8>,
+    border: Border,
+    op: impl Fn(&[bool; 9]) -> bool,
+) -> OImage<Mono8> {
+    let rows: Vec<&[u8]> = src.rowchunks_exact().collect();
+    let (width, height) = (src.width() as i64, rows.len() as i64);
+    let is_foreground = |x: i64, y: i64| {
+        if x < 0 || y < 0 || x >= width || y >= height {
+            border == Border::Foreground
+        } else {
+            rows[y as usize][x as usize] != 0
+        }
+    };
+    let mut buf = Vec::with_capacity(width as usize * height as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let mut flags = [false; 9];
+            for (i, flag) in flags.iter_mut().enumerate() {
+                let (dx, dy) = (i as i64 % 3 - 1, i as i64 / 3 - 1);
+                *flag = is_foreground(x + dx, y + dy);
+            }
+            buf.push(if op(&flags) { 255 } else { 0 });
+        }
+    }
+    OImage::from_parts(src.width(), height as u32, width as usize, buf)
+}
+
+/// Erode a binary mask with a 3x3 square structuring element.
+///
+/// Nonzero pixels are foreground. An output pixel is foreground (255) only if
+/// all pixels in its 3x3 neighborhood are foreground; otherwise it is 0.
+/// `border` sets how pixels outside the image are treated. The output is
+/// tightly packed.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn erode3x3_mono8(src: &dyn ImageStride<Mono8>, border: Border) -> OImage<Mono8> {
+    morph3x3_mono8(src, border, |flags| flags.iter().all(|f| *f))
+}
+
+/// Dilate a binary mask with a 3x3 square structuring element.
+///
+/// Nonzero pixels are foreground. An output pixel is foreground (255) if any
+/// pixel in its 3x3 neighborhood is foreground; otherwise it is 0. `border`
+/// sets how pixels outside the image are treated. The output is tightly
+/// packed.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn dilate3x3_mono8(src: &dyn ImageStride<Mono8>, border: Border) -> OImage<Mono8> {
+    morph3x3_mono8(src, border, |flags| flags.iter().any(|f| *f))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        image_ref::{ImageRef, ImageRefMut},
+        ImageData,
+    };
+
+    #[test]
+    fn test_blend_rgba_over_rgb() {
+        let fg_buf = [255, 0, 0, 128, 255, 0, 0, 0, 255, 0, 0, 255];
+        let fg = ImageRef::<RGBA8>::new(3, 1, 12, &fg_buf).unwrap();
+        let mut bg_buf = [0, 0, 255, 0, 0, 255, 0, 0, 255];
+        {
+            let mut bg = ImageRefMut::<RGB8>::new(3, 1, 9, &mut bg_buf).unwrap();
+            blend_rgba_over_rgb(&fg, &mut bg).unwrap();
+        }
+        // 50% alpha mixes, zero alpha keeps the background and full alpha
+        // replaces it.
+        assert_eq!(bg_buf, [128, 0, 127, 0, 0, 255, 255, 0, 0]);
+    }
+
+    #[test]
+    fn test_saturating_mono8() {
+        let a_buf = [10, 200, 0, 0, 100, 255];
+        let b_buf = [20, 100, 50, 0];
+        let a = ImageRef::<Mono8>::new(2, 2, 4, &a_buf).unwrap();
+        let b = ImageRef::<Mono8>::new(2, 2, 2, &b_buf).unwrap();
+
+        let diff = subtract_saturating(&a, &b).unwrap();
+        assert_eq!(diff.image_data(), &[0, 100, 50, 255]);
+        let sum = add_saturating(&a, &b).unwrap();
+        assert_eq!(sum.image_data(), &[30, 255, 150, 255]);
+
+        let c = ImageRef::<Mono8>::new(1, 2, 1, &b_buf).unwrap();
+        assert_eq!(subtract_saturating(&a, &c).unwrap_err(), DimMismatch);
+    }
+
+    #[test]
+    fn test_saturating_mono16() {
+        let to_bytes = |v: &[u16]| -> Vec<u8> { v.iter().flat_map(|x| x.to_le_bytes()).collect() };
+        let a_buf = to_bytes(&[10, 300, 65000]);
+        let b_buf = to_bytes(&[20, 256, 1000]);
+        let a = ImageRef::<Mono16>::new(3, 1, 6, &a_buf).unwrap();
+        let b = ImageRef::<Mono16>::new(3, 1, 6, &b_buf).unwrap();
+
+        let diff = subtract_saturating_mono16(&a, &b).unwrap();
+        assert_eq!(diff.image_data(), &to_bytes(&[0, 44, 64000])[..]);
+        let sum = add_saturating_mono16(&a, &b).unwrap();
+        assert_eq!(sum.image_data(), &to_bytes(&[30, 556, 65535])[..]);
+    }
+
+    #[test]
+    fn test_threshold_mono8() {
+        let buf: Vec<u8> = (0..8).map(|x| x * 10).collect();
+        let gradient = ImageRef::<Mono8>::new(8, 1, 8, &buf).unwrap();
+
+        let mask = threshold_mono8(&gradient, 30, 255, 0);
+        assert_eq!(mask.image_data(), &[0, 0, 0, 0, 255, 255, 255, 255]);
+
+        let mask = threshold_mono8_cmp(&gradient, 30, Comparison::LessOrEqual, 1, 0);
+        assert_eq!(mask.image_data(), &[1, 1, 1, 1, 0, 0, 0, 0]);
+
+        let mask = threshold_mono8_cmp(&gradient, 30, Comparison::GreaterOrEqual, 1, 0);
+        assert_eq!(mask.image_data(), &[0, 0, 0, 1, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_first_nonzero_mono8() {
+        // The nonzero padding byte after the first row is ignored.
+        let mut buf = [0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0];
+        let im = ImageRef::<Mono8>::new(3, 3, 4, &buf).unwrap();
+        assert_eq!(first_nonzero_mono8(&im), None);
+
+        buf[6] = 1;
+        buf[9] = 2;
+        let im = ImageRef::<Mono8>::new(3, 3, 4, &buf).unwrap();
+        assert_eq!(first_nonzero_mono8(&im), Some((2, 1)));
+    }
+
+    #[test]
+    fn test_apply_lut() {
+        let buf = [0, 64, 128, 255, 9];
+        let im = ImageRef::<Mono8>::new(2, 2, 3, &buf).unwrap();
+        let mut identity = [0u8; 256];
+        for (i, x) in identity.iter_mut().enumerate() {
+            *x = i as u8;
+        }
+        assert_eq!(gamma_lut(1.0)[..], identity[..]);
+        assert_eq!(
+            apply_lut_mono8(&im, &identity).image_data(),
+            &[0, 64, 255, 9]
+        );
+
+        let gamma = gamma_lut(2.2);
+        assert_eq!(
+            apply_lut_mono8(&im, &gamma).image_data(),
+            &[0, 136, 255, 56]
+        );
+
+        let rgb = ImageRef::<RGB8>::new(1, 1, 3, &buf[..3]).unwrap();
+        assert_eq!(apply_lut_rgb8(&rgb, &gamma).image_data(), &[0, 136, 186]);
+    }
+
+    #[test]
+    fn test_auto_contrast() {
+        let buf: Vec<u8> = (0..100).map(|i| 100 + (i % 20) as u8).collect();
+        let im = ImageRef::<Mono8>::new(10, 10, 10, &buf).unwrap();
+        let out = auto_contrast_mono8(&im, 0.0, 100.0);
+        let data = out.image_data();
+        assert_eq!(data.iter().min(), Some(&0));
+        assert_eq!(data.iter().max(), Some(&255));
+        assert_eq!(data[0], 0);
+        assert_eq!(data[19], 255);
+
+        // Clipping the extremes saturates them.
+        let out = auto_contrast_mono8(&im, 10.0, 90.0);
+        assert_eq!(out.image_data()[0], 0);
+        assert_eq!(out.image_data()[1], 0);
+        assert_eq!(out.image_data()[18], 255);
+
+        let flat = ImageRef::<Mono8>::new(2, 1, 2, &[5, 5]).unwrap();
+        assert_eq!(auto_contrast_mono8(&flat, 1.0, 99.0).image_data(), &[5, 5]);
+    }
+
+    #[test]
+    fn test_same_geometry() {
+        let buf = [0u8; 6];
+        let a = ImageRef::<Mono8>::new(2, 3, 2, &buf).unwrap();
+        let b = ImageRef::<Mono8>::new(2, 3, 2, &buf).unwrap();
+        let c = ImageRef::<Mono8>::new(3, 2, 3, &buf).unwrap();
+        assert!(same_geometry(&a, &b));
+        assert!(!same_geometry(&a, &c));
+    }
+
+    #[test]
+    fn test_erode_dilate() {
+        #[rustfmt::skip]
+        let buf = [
+            0, 0, 0, 0, 0, 9,
+            0, 7, 0, 0, 0, 9,
+            0, 0, 0, 0, 0, 9,
+            0, 0, 0, 0, 0,
+        ];
+        let im = ImageRef::<Mono8>::new(5, 4, 6, &buf).unwrap();
+
+        // An isolated pixel disappears under erosion.
+        let eroded = erode3x3_mono8(&im, Border::Background);
+        assert_eq!(crate::Stride::stride(&eroded), 5);
+        assert!(eroded.image_data().iter().all(|x| *x == 0));
+
+        #[rustfmt::skip]
+        let expected = [
+            255, 255, 255, 0, 0,
+            255, 255, 255, 0, 0,
+            255, 255, 255, 0, 0,
+            0, 0, 0, 0, 0,
+        ];
+        let dilated = dilate3x3_mono8(&im, Border::Background);
+        assert_eq!(dilated.image_data(), &expected[..]);
+
+        // A full mask survives erosion only where the border does not reach.
+        let full = ImageRef::<Mono8>::new(3, 3, 3, &[1; 9]).unwrap();
+        let eroded = erode3x3_mono8(&full, Border::Background);
+        assert_eq!(eroded.image_data(), &[0, 0, 0, 0, 255, 0, 0, 0, 0]);
+        let eroded = erode3x3_mono8(&full, Border::Foreground);
+        assert_eq!(eroded.image_data(), &[255; 9]);
+
+        let empty = ImageRef::<Mono8>::new(3, 3, 3, &[0; 9]).unwrap();
+        let dilated = dilate3x3_mono8(&empty, Border::Foreground);
+        assert_eq!(
+            dilated.image_data(),
+            &[255, 255, 255, 255, 0, 255, 255, 255, 255]
+        );
+    }
+
+    #[test]
+    fn test_blend_dimension_mismatch() {
+        let fg_buf = [0u8; 8];
+        let fg = ImageRef::<RGBA8>::new(2, 1, 8, &fg_buf).unwrap();
+        let mut bg_buf = [0u8; 3];
+        let mut bg = ImageRefMut::<RGB8>::new(1, 1, 3, &mut bg_buf).unwrap();
+        assert_eq!(
+            blend_rgba_over_rgb(&fg, &mut bg),
+            Err(BlendError::DimensionMismatch)
+        );
+        assert_eq!(bg.image_data(), &[0, 0, 0]);
+    }
+}
@@ -8,6 +8,10 @@ use alloc::vec;
 use alloc::vec::Vec;
 
 use crate::{
+    error::{AppendError, BufferError, BuildError, CopyError},
+    image_ref::{ImageRef, ImageRefMut},
+    iter::{HasRowChunksExact, HasRowChunksExactMut},
+    pixel_format::{Mono8, RGB8},
     ImageBuffer, ImageBufferMutRef, ImageBufferRef, ImageData, ImageMutData, OwnedImageStride,
     PixelFormat, Stride,
 };
@@ -15,6 +19,10 @@ use crate::{
 // -----
 
 /// An owned image buffer with pixel format `FMT`.
+///
+/// `OImage<FMT>` is `Send + Sync + 'static` when `FMT` is, which is the case
+/// for all pixel formats defined in this crate, so it can be moved across
+/// threads and channels.
 #[derive(Clone)]
 pub struct OImage<FMT: PixelFormat> {
     buf: Vec<u8>,
@@ -62,17 +70,7 @@ impl<FMT: PixelFormat> OImage<FMT> {
     /// Returns None if the buffer is not large enough to store an image of the
     /// desired properties.
     pub fn new(width: u32, height: u32, stride: usize, buf: Vec<u8>) -> Option<Self> {
-        let fmt = crate::pixel_format::pixfmt::<FMT>().unwrap();
-        let min_stride = fmt.bits_per_pixel() as usize * width as usize / 8;
-
-        if height == 0 {
-            return None;
-        }
-        let sz = stride * (height as usize - 1) + min_stride;
-
-        if buf.len() < sz {
-            return None;
-        }
+        check_buffer_size::<FMT>(width, height, stride, buf.len()).ok()?;
         Some(Self {
             width,
             height,
@@ -82,10 +80,77 @@ impl<FMT: PixelFormat> OImage<FMT> {
         })
     }
 
+    /// Move a `Vec<u8>` buffer as the backing store without checking its size.
+    ///
+    /// This skips the validation done by [OImage::new] for use in hot loops
+    /// where the buffer size is known to be correct.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee that `height` is not zero and that
+    /// `buf.len() >= stride * (height - 1) + valid_stride`, where
+    /// `valid_stride` is the number of valid bytes in a row. The methods of
+    /// this crate do not cause undefined behavior for undersized buffers, but
+    /// other code may rely on this invariant in `unsafe` blocks.
+    pub unsafe fn new_unchecked(width: u32, height: u32, stride: usize, buf: Vec<u8>) -> Self {
+        Self {
+            width,
+            height,
+            stride,
+            buf,
+            fmt: std::marker::PhantomData,
+        }
+    }
+
+    /// Reinterpret the existing buffer with new dimensions and stride.
+    ///
+    /// The buffer is reused without copying. If it is not large enough for the
+    /// new geometry, the original image is returned along with the error.
+    pub fn try_reshape(
+        self,
+        width: u32,
+        height: u32,
+        stride: usize,
+    ) -> Result<Self, (Self, BufferError)> {
+        match check_buffer_size::<FMT>(width, height, stride, self.buf.len()) {
+            Ok(()) => Ok(Self {
+                width,
+                height,
+                stride,
+                ..self
+            }),
+            Err(e) => Err((self, e)),
+        }
+    }
+
+    /// Reinterpret the buffer as pixel format `DST` without copying.
+    ///
+    /// The width, height and stride are kept. This fails if a row of `DST`
+    /// pixels does not fit within the stride or the buffer is too small, in
+    /// which case the original image is returned along with the error.
+    pub fn reinterpret<DST: PixelFormat>(self) -> Result<OImage<DST>, (Self, BufferError)> {
+        let min_stride = crate::min_stride::<DST>(self.width);
+        if self.stride < min_stride {
+            let e = BufferError::StrideTooSmall {
+                required: min_stride,
+                actual: self.stride,
+            };
+            return Err((self, e));
+        }
+        match check_buffer_size::<DST>(self.width, self.height, self.stride, self.buf.len()) {
+            Ok(()) => Ok(OImage::from_parts(
+                self.width,
+                self.height,
+                self.stride,
+                self.buf,
+            )),
+            Err(e) => Err((self, e)),
+        }
+    }
+
     /// Allocate minimum size buffer for image and fill with zeros
     pub fn zeros(width: u32, height: u32, stride: usize) -> Option<Self> {
-        let fmt = crate::pixel_format::pixfmt::<FMT>().unwrap();
-        let valid_stride = fmt.bits_per_pixel() as usize * width as usize / 8;
+        let valid_stride = crate::min_stride::<FMT>(width);
 
         let sz = stride * (height as usize - 1) + valid_stride;
         let buf = vec![0u8; sz];
@@ -98,6 +163,118 @@ impl<FMT: PixelFormat> OImage<FMT> {
         })
     }
 
+    /// Allocate a buffer of exactly the required length for reuse as a frame
+    /// buffer.
+    ///
+    /// The buffer has the same length as one from [OImage::zeros] and is also
+    /// zeroed; it is intended to be filled row by row, e.g. with
+    /// `rowchunks_exact_mut`, or reshaped with [OImage::try_reshape] to any
+    /// geometry needing at most as many bytes. Returns None if `height` is
+    /// zero, `stride` is smaller than a row or the size overflows.
+    pub fn with_capacity(width: u32, height: u32, stride: usize) -> Option<Self> {
+        let min_stride = crate::min_stride::<FMT>(width);
+        if height == 0 || stride < min_stride {
+            return None;
+        }
+        let len = stride
+            .checked_mul(height as usize - 1)?
+            .checked_add(min_stride)?;
+        Some(Self::from_parts(width, height, stride, vec![0u8; len]))
+    }
+
+    /// Copy into a new image with stride `target_stride`, zero-filling the
+    /// padding.
+    ///
+    /// The new buffer also includes padding after the last row. Returns None if
+    /// `target_stride` is smaller than the number of valid bytes in a row.
+    pub fn pad_to_stride(&self, target_stride: usize) -> Option<Self> {
+        let valid_stride = crate::min_stride::<FMT>(self.width);
+        if target_stride < valid_stride {
+            return None;
+        }
+        let mut buf = vec![0u8; target_stride * self.height as usize];
+        if target_stride > 0 {
+            for (dest, src) in buf
+                .chunks_exact_mut(target_stride)
+                .zip(self.rowchunks_exact())
+            {
+                dest[..valid_stride].copy_from_slice(src);
+            }
+        }
+        Some(Self::from_parts(
+            self.width,
+            self.height,
+            target_stride,
+            buf,
+        ))
+    }
+
+    /// The smallest stride which holds a row and is a multiple of `alignment`.
+    ///
+    /// Use with [OImage::pad_to_stride]. Returns None if `alignment` is zero.
+    pub fn align_stride_to(&self, alignment: usize) -> Option<usize> {
+        if alignment == 0 {
+            return None;
+        }
+        let valid_stride = crate::min_stride::<FMT>(self.width);
+        let remainder = valid_stride % alignment;
+        if remainder == 0 {
+            Some(valid_stride)
+        } else {
+            valid_stride.checked_add(alignment - remainder)
+        }
+    }
+
+    /// Append `num_rows` rows to the bottom of the image, increasing its
+    /// height.
+    ///
+    /// `rows` holds the new rows with the image stride, so it must be exactly
+    /// `num_rows * stride` bytes long, including the padding of the last row.
+    /// The padding after the current last row is zero-filled if the buffer
+    /// does not include it, and any bytes beyond it are discarded. On error,
+    /// the image is unchanged.
+    pub fn append_rows(&mut self, rows: &[u8], num_rows: u32) -> Result<(), AppendError> {
+        let expected = self
+            .stride
+            .checked_mul(num_rows as usize)
+            .ok_or(AppendError::Overflow)?;
+        if rows.len() != expected {
+            return Err(AppendError::Length {
+                expected,
+                actual: rows.len(),
+            });
+        }
+        let height = self
+            .height
+            .checked_add(num_rows)
+            .ok_or(AppendError::Overflow)?;
+        let current_len = self
+            .stride
+            .checked_mul(self.height as usize)
+            .ok_or(AppendError::Overflow)?;
+        let new_len = current_len
+            .checked_add(expected)
+            .ok_or(AppendError::Overflow)?;
+        self.buf.resize(current_len, 0);
+        self.buf.extend_from_slice(rows);
+        debug_assert_eq!(self.buf.len(), new_len);
+        self.height = height;
+        Ok(())
+    }
+
+    /// Move a `Vec<u8>` buffer into an image without checking its size.
+    ///
+    /// Callers within this crate must ensure the buffer is large enough.
+    pub(crate) fn from_parts(width: u32, height: u32, stride: usize, buf: Vec<u8>) -> Self {
+        Self {
+            width,
+            height,
+            stride,
+            buf,
+            fmt: std::marker::PhantomData,
+        }
+    }
+
     pub fn from_owned(orig: impl OwnedImageStride<FMT>) -> Self {
         let width = orig.width();
         let height = orig.height();
@@ -107,12 +284,149 @@ impl<FMT: PixelFormat> OImage<FMT> {
     }
 }
 
+/// Magic bytes at the start of an MVF file.
+#[cfg(feature = "std")]
+const MVF_MAGIC: &[u8; 4] = b"MVF1";
+
+#[cfg(feature = "std")]
+impl<FMT: PixelFormat> OImage<FMT> {
+    /// Write the image in the simple, self-describing MVF format.
+    ///
+    /// The format is the magic bytes `MVF1`, the pixel format name as a length
+    /// byte followed by the name, then width (`u32`), height (`u32`), stride
+    /// (`u64`) and buffer length (`u64`), all little-endian, followed by the
+    /// raw buffer.
+    pub fn write_mvf<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        let fmt = crate::pixel_format::pixfmt::<FMT>()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        let name = fmt.as_str().as_bytes();
+        w.write_all(MVF_MAGIC)?;
+        w.write_all(&[name.len() as u8])?;
+        w.write_all(name)?;
+        w.write_all(&self.width.to_le_bytes())?;
+        w.write_all(&self.height.to_le_bytes())?;
+        w.write_all(&(self.stride as u64).to_le_bytes())?;
+        w.write_all(&(self.buf.len() as u64).to_le_bytes())?;
+        w.write_all(&self.buf)
+    }
+
+    /// Read an image written by [OImage::write_mvf].
+    ///
+    /// The pixel format is chosen by the caller through `FMT` rather than
+    /// returned as a runtime-typed image, as this crate has no such type. The
+    /// format stored in the header is checked against `FMT`.
+    ///
+    /// Returns an error of kind `InvalidData` if the magic bytes are wrong, the
+    /// stored pixel format is not `FMT`, the stored sizes overflow or the
+    /// buffer is shorter than the stored dimensions require.
+    pub fn read_mvf<R: std::io::Read>(r: &mut R) -> std::io::Result<Self> {
+        use std::convert::TryFrom;
+        use std::io::{Error, ErrorKind, Read};
+        fn read_array<R: std::io::Read, const N: usize>(r: &mut R) -> std::io::Result<[u8; N]> {
+            let mut buf = [0u8; N];
+            r.read_exact(&mut buf)?;
+            Ok(buf)
+        }
+
+        if &read_array::<_, 4>(r)? != MVF_MAGIC {
+            return Err(Error::new(ErrorKind::InvalidData, "not an MVF file"));
+        }
+        let [name_len] = read_array::<_, 1>(r)?;
+        let mut name = vec![0u8; name_len as usize];
+        r.read_exact(&mut name)?;
+        let expected = crate::pixel_format::pixfmt::<FMT>()
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        if name != expected.as_str().as_bytes() {
+            return Err(Error::new(ErrorKind::InvalidData, "pixel format mismatch"));
+        }
+        let width = u32::from_le_bytes(read_array(r)?);
+        let height = u32::from_le_bytes(read_array(r)?);
+        let to_usize = |v: u64| {
+            usize::try_from(v)
+                .map_err(|_| Error::new(ErrorKind::InvalidData, BufferError::Overflow))
+        };
+        let stride = to_usize(u64::from_le_bytes(read_array(r)?))?;
+        let len = to_usize(u64::from_le_bytes(read_array(r)?))?;
+        check_buffer_size::<FMT>(width, height, stride, len)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        // Do not trust `len` for the allocation: grow only as data arrives.
+        let mut buf = Vec::new();
+        r.take(len as u64).read_to_end(&mut buf)?;
+        if buf.len() < len {
+            return Err(Error::new(ErrorKind::InvalidData, "truncated MVF buffer"));
+        }
+        Ok(Self::from_parts(width, height, stride, buf))
+    }
+
+    /// Write the raw image buffer, including any row padding, to a file.
+    ///
+    /// Nothing but the buffer is stored, so the dimensions, stride and pixel
+    /// format must be known when reading it back with [OImage::load_raw].
+    pub fn save_raw<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, &self.buf)
+    }
+
+    /// Read an image from a file holding a raw image buffer.
+    ///
+    /// Returns an error of kind `InvalidData` if the file is too small for the
+    /// given dimensions and stride.
+    pub fn load_raw<P: AsRef<std::path::Path>>(
+        path: P,
+        width: u32,
+        height: u32,
+        stride: usize,
+    ) -> std::io::Result<Self> {
+        let buf = std::fs::read(path)?;
+        check_buffer_size::<FMT>(width, height, stride, buf.len())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        Ok(Self::from_parts(width, height, stride, buf))
+    }
+}
+
+/// Check that a buffer of `len` bytes can store an image of the desired
+/// properties.
+fn check_buffer_size<FMT: PixelFormat>(
+    width: u32,
+    height: u32,
+    stride: usize,
+    len: usize,
+) -> Result<(), BufferError> {
+    let min_stride = crate::min_stride::<FMT>(width);
+
+    if height == 0 {
+        return Err(BufferError::ZeroHeight);
+    }
+    let required = crate::roi_byte_offset(0, height - 1, stride, 0)
+        .and_then(|offset| offset.checked_add(min_stride))
+        .ok_or(BufferError::Overflow)?;
+
+    if len < required {
+        return Err(BufferError::TooSmall {
+            required,
+            actual: len,
+        });
+    }
+    Ok(())
+}
+
 /// Compile-time test to ensure ImageStruct implements Send.
 fn _test_owned_image_implements_send<F: PixelFormat + Send>() {
     fn implements<T: Send>() {}
     implements::<OImage<F>>();
 }
 
+/// Compile-time test to ensure ImageStruct implements Sync.
+fn _test_owned_image_implements_sync<F: PixelFormat + Sync>() {
+    fn implements<T: Sync>() {}
+    implements::<OImage<F>>();
+}
+
+/// Compile-time test to ensure ImageStruct is `'static`.
+fn _test_owned_image_implements_static<F: PixelFormat>() {
+    fn implements<T: 'static>() {}
+    implements::<OImage<F>>();
+}
+
 /// Compile-time test to ensure ImageStruct implements Stride.
 fn _test_owned_image_implements_stride<F: PixelFormat>() {
     fn implements<T: Stride, F>() {}
@@ -136,8 +450,14 @@ impl<F: PixelFormat> std::fmt::Debug for OImage<F> {
     }
 }
 
+impl<F: PixelFormat> std::fmt::Display for OImage<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        crate::fmt_summary::<F>(f, self.width, self.height, self.stride)
+    }
+}
+
 impl<F: PixelFormat> OImage<F> {
-    pub fn copy_from<FRAME: crate::ImageStride<F>>(frame: &FRAME) -> OImage<F> {
+    pub fn copy_from<FRAME: crate::ImageStride<F> + ?Sized>(frame: &FRAME) -> OImage<F> {
         let width = frame.width();
         let height = frame.height();
         let stride = frame.stride();
@@ -153,6 +473,242 @@ impl<F: PixelFormat> OImage<F> {
     }
 }
 
+impl<F: PixelFormat> OImage<F> {
+    /// Resize to `new_width` x `new_height` using nearest-neighbor sampling.
+    ///
+    /// The output is tightly packed. Returns None if either new dimension is
+    /// zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pixel format does not use a whole number of bytes per
+    /// pixel.
+    pub fn resize_nearest(&self, new_width: u32, new_height: u32) -> Option<OImage<F>> {
+        assert!(
+            F::BITS_PER_PIXEL % 8 == 0,
+            "pixel format must use whole bytes per pixel"
+        );
+        if new_width == 0 || new_height == 0 {
+            return None;
+        }
+        let bytes_per_pixel = F::BITS_PER_PIXEL as usize / 8;
+        let stride = new_width as usize * bytes_per_pixel;
+        let mut buf = Vec::with_capacity(stride * new_height as usize);
+        if self.width > 0 && self.height > 0 {
+            let rows: Vec<&[u8]> = self.rowchunks_exact().collect();
+            let nearest = |dst: u32, dst_len: u32, src_len: u32| -> usize {
+                (dst as u64 * src_len as u64 / dst_len as u64) as usize
+            };
+            for y in 0..new_height {
+                let row = rows[nearest(y, new_height, self.height)];
+                for x in 0..new_width {
+                    let start = nearest(x, new_width, self.width) * bytes_per_pixel;
+                    buf.extend_from_slice(&row[start..start + bytes_per_pixel]);
+                }
+            }
+        } else {
+            buf.resize(stride * new_height as usize, 0);
+        }
+        Some(OImage::from_parts(new_width, new_height, stride, buf))
+    }
+}
+
+/// Bilinear resize of an image with `channels` bytes per pixel.
+///
+/// Pixel centers are aligned, so corner pixels of the output equal the
+/// corner pixels of the source. Returns None if either new dimension is zero.
+fn resize_bilinear_u8<F: PixelFormat>(
+    src: &OImage<F>,
+    channels: usize,
+    new_width: u32,
+    new_height: u32,
+) -> Option<OImage<F>> {
+    if new_width == 0 || new_height == 0 {
+        return None;
+    }
+    let stride = new_width as usize * channels;
+    let mut buf = Vec::with_capacity(stride * new_height as usize);
+    if src.width == 0 || src.height == 0 {
+        buf.resize(stride * new_height as usize, 0);
+        return Some(OImage::from_parts(new_width, new_height, stride, buf));
+    }
+    let rows: Vec<&[u8]> = src.rowchunks_exact().collect();
+    // The source coordinate of a destination pixel center, as the index of
+    // the lower neighbor and the weight of the upper neighbor.
+    let sample = |dst: u32, dst_len: u32, src_len: u32| -> (usize, usize, f32) {
+        let pos = (dst as f32 + 0.5) * src_len as f32 / dst_len as f32 - 0.5;
+        let pos = pos.clamp(0.0, (src_len - 1) as f32);
+        let lo = pos as usize;
+        let hi = (lo + 1).min(src_len as usize - 1);
+        (lo, hi, pos - lo as f32)
+    };
+    for y in 0..new_height {
+        let (y0, y1, wy) = sample(y, new_height, src.height);
+        for x in 0..new_width {
+            let (x0, x1, wx) = sample(x, new_width, src.width);
+            for c in 0..channels {
+                let px = |row: &[u8], col: usize| row[col * channels + c] as f32;
+                let top = px(rows[y0], x0) * (1.0 - wx) + px(rows[y0], x1) * wx;
+                let bottom = px(rows[y1], x0) * (1.0 - wx) + px(rows[y1], x1) * wx;
+                let value = top * (1.0 - wy) + bottom * wy;
+                buf.push((value + 0.5) as u8);
+            }
+        }
+    }
+    Some(OImage::from_parts(new_width, new_height, stride, buf))
+}
+
+impl OImage<Mono8> {
+    /// Create a horizontal ramp from 0 in the left column to 255 in the right
+    /// column.
+    ///
+    /// The output is tightly packed. This is useful as a test pattern.
+    /// Returns None if `height` is zero.
+    pub fn gradient(width: u32, height: u32) -> Option<OImage<Mono8>> {
+        if height == 0 {
+            return None;
+        }
+        let denom = width.saturating_sub(1).max(1) as u64;
+        let row: Vec<u8> = (0..width as u64).map(|x| (x * 255 / denom) as u8).collect();
+        let mut buf = Vec::with_capacity(row.len() * height as usize);
+        for _ in 0..height {
+            buf.extend_from_slice(&row);
+        }
+        Some(OImage::from_parts(width, height, width as usize, buf))
+    }
+
+    /// Resize to `new_width` x `new_height` using bilinear interpolation.
+    ///
+    /// The output is tightly packed. Returns None if either new dimension is
+    /// zero.
+    pub fn resize_bilinear(&self, new_width: u32, new_height: u32) -> Option<OImage<Mono8>> {
+        resize_bilinear_u8(self, 1, new_width, new_height)
+    }
+}
+
+impl OImage<RGB8> {
+    /// Create seven vertical color bars similar to the SMPTE test pattern.
+    ///
+    /// From left to right the bars are white, yellow, cyan, green, magenta,
+    /// red and blue at 75% intensity. The output is tightly packed. Returns
+    /// None if `height` is zero.
+    pub fn color_bars(width: u32, height: u32) -> Option<OImage<RGB8>> {
+        if height == 0 {
+            return None;
+        }
+        const BARS: [[u8; 3]; 7] = [
+            [191, 191, 191],
+            [191, 191, 0],
+            [0, 191, 191],
+            [0, 191, 0],
+            [191, 0, 191],
+            [191, 0, 0],
+            [0, 0, 191],
+        ];
+        let row: Vec<u8> = (0..width as u64)
+            .flat_map(|x| BARS[(x * 7 / width as u64) as usize].iter().copied())
+            .collect();
+        let mut buf = Vec::with_capacity(row.len() * height as usize);
+        for _ in 0..height {
+            buf.extend_from_slice(&row);
+        }
+        Some(OImage::from_parts(width, height, width as usize * 3, buf))
+    }
+
+    /// Resize to `new_width` x `new_height` using bilinear interpolation.
+    ///
+    /// The output is tightly packed. Returns None if either new dimension is
+    /// zero.
+    pub fn resize_bilinear(&self, new_width: u32, new_height: u32) -> Option<OImage<RGB8>> {
+        resize_bilinear_u8(self, 3, new_width, new_height)
+    }
+}
+
+/// Copy `src` into the top-left corner of `dst`.
+///
+/// `dst` may be larger than `src`, e.g. a preallocated buffer for the largest
+/// expected image. Pixels of `dst` outside the copied region are left
+/// unchanged. Returns an error if `src` is wider or taller than `dst`.
+pub fn copy_into_fixed<F: PixelFormat>(
+    src: &dyn crate::ImageStride<F>,
+    dst: &mut OImage<F>,
+) -> Result<(), CopyError> {
+    if src.width() > dst.width || src.height() > dst.height {
+        return Err(CopyError::DoesNotFit);
+    }
+    for (src_row, dst_row) in src.rowchunks_exact().zip(dst.rowchunks_exact_mut()) {
+        dst_row[..src_row.len()].copy_from_slice(src_row);
+    }
+    Ok(())
+}
+
+/// Images are equal if their dimensions and valid pixels are equal.
+///
+/// The stride and any row padding are ignored.
+impl<F: PixelFormat> PartialEq for OImage<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.rowchunks_exact().eq(other.rowchunks_exact())
+    }
+}
+
+impl<F: PixelFormat> Eq for OImage<F> {}
+
+/// Hashes the dimensions, pixel format and valid pixels.
+///
+/// The stride and any row padding are ignored, consistent with `PartialEq`.
+impl<F: PixelFormat> std::hash::Hash for OImage<F> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.width.hash(state);
+        self.height.hash(state);
+        crate::pixel_format::pixfmt::<F>().ok().hash(state);
+        for row in self.rowchunks_exact() {
+            row.hash(state);
+        }
+    }
+}
+
+impl<F: PixelFormat> From<&dyn crate::ImageStride<F>> for OImage<F> {
+    fn from(frame: &dyn crate::ImageStride<F>) -> OImage<F> {
+        // copy the data
+        OImage::copy_from(frame)
+    }
+}
+
+impl<F: PixelFormat> From<ImageRef<'_, F>> for OImage<F> {
+    fn from(frame: ImageRef<'_, F>) -> OImage<F> {
+        // copy the data
+        OImage::copy_from(&frame)
+    }
+}
+
+impl<F: PixelFormat> From<ImageRefMut<'_, F>> for OImage<F> {
+    fn from(frame: ImageRefMut<'_, F>) -> OImage<F> {
+        // copy the data
+        OImage::copy_from(&frame)
+    }
+}
+
+/// Construct from `(width, height, stride, buf)`, checking the buffer size.
+impl<F: PixelFormat> std::convert::TryFrom<(u32, u32, usize, Vec<u8>)> for OImage<F> {
+    type Error = BufferError;
+    fn try_from(parts: (u32, u32, usize, Vec<u8>)) -> Result<Self, Self::Error> {
+        let (width, height, stride, buf) = parts;
+        check_buffer_size::<F>(width, height, stride, buf.len())?;
+        Ok(Self::from_parts(width, height, stride, buf))
+    }
+}
+
+/// Iterate over the valid bytes of each row.
+impl<'a, F: PixelFormat> IntoIterator for &'a OImage<F> {
+    type Item = &'a [u8];
+    type IntoIter = crate::iter::RowChunksExact<'a>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.rowchunks_exact()
+    }
+}
+
 impl<F: PixelFormat> From<OImage<F>> for Vec<u8> {
     fn from(orig: OImage<F>) -> Vec<u8> {
         orig.buf
@@ -164,3 +720,754 @@ impl<F: PixelFormat> From<Box<OImage<F>>> for Vec<u8> {
         orig.buf
     }
 }
+
+// -----
+
+/// A read-only image backed by a memory-mapped file.
+///
+/// This behaves like [ImageRef] but owns the mapping, which allows working
+/// with very large raw recordings without loading them into memory.
+#[cfg(feature = "mmap")]
+pub struct MmapImage<F: PixelFormat> {
+    mmap: memmap2::Mmap,
+    width: u32,
+    height: u32,
+    stride: usize,
+    fmt: std::marker::PhantomData<F>,
+}
+
+#[cfg(feature = "mmap")]
+impl<F: PixelFormat> MmapImage<F> {
+    /// Use a memory mapping as the backing store for an image.
+    ///
+    /// Creating the mapping is `unsafe` in [memmap2] because the file may be
+    /// modified while mapped; see [memmap2::Mmap::map].
+    ///
+    /// Returns None if the mapping is not large enough to store an image of
+    /// the desired properties.
+    pub fn new(width: u32, height: u32, stride: usize, mmap: memmap2::Mmap) -> Option<Self> {
+        check_buffer_size::<F>(width, height, stride, mmap.len()).ok()?;
+        Some(Self {
+            mmap,
+            width,
+            height,
+            stride,
+            fmt: std::marker::PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<F: PixelFormat> ImageData<F> for MmapImage<F> {
+    fn width(&self) -> u32 {
+        self.width
+    }
+    fn height(&self) -> u32 {
+        self.height
+    }
+    fn buffer_ref(&self) -> ImageBufferRef<'_, F> {
+        ImageBufferRef::new(&self.mmap[..])
+    }
+    fn buffer(self) -> ImageBuffer<F> {
+        // copy the data
+        self.buffer_ref().to_buffer()
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<F: PixelFormat> Stride for MmapImage<F> {
+    fn stride(&self) -> usize {
+        self.stride
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<F: PixelFormat> std::fmt::Debug for MmapImage<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("MmapImage")
+            .field("fmt", &self.fmt)
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("stride", &self.stride)
+            .finish_non_exhaustive()
+    }
+}
+
+// -----
+
+/// Builds an [OImage] one row at a time.
+///
+/// Rows are pushed from top to bottom and must contain exactly the valid bytes
+/// of a row. Padding up to the stride is filled with zeros.
+pub struct OImageBuilder<F: PixelFormat> {
+    buf: Vec<u8>,
+    width: u32,
+    height: u32,
+    stride: usize,
+    rows: u32,
+    fmt: std::marker::PhantomData<F>,
+}
+
+impl<F: PixelFormat> OImageBuilder<F> {
+    /// Start building an image of the given dimensions and stride.
+    pub fn new(width: u32, height: u32, stride: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            width,
+            height,
+            stride,
+            rows: 0,
+            fmt: std::marker::PhantomData,
+        }
+    }
+
+    /// Append the next row.
+    pub fn push_row(&mut self, row: &[u8]) -> Result<(), BuildError> {
+        let valid_stride = crate::min_stride::<F>(self.width);
+        if self.stride < valid_stride {
+            return Err(BuildError::StrideTooSmall);
+        }
+        if self.rows >= self.height {
+            return Err(BuildError::TooManyRows);
+        }
+        if row.len() != valid_stride {
+            return Err(BuildError::RowLength {
+                expected: valid_stride,
+                actual: row.len(),
+            });
+        }
+        if self.rows > 0 {
+            self.buf.resize(self.rows as usize * self.stride, 0);
+        }
+        self.buf.extend_from_slice(row);
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Finish building the image.
+    ///
+    /// Returns an error if fewer rows than the image height were pushed.
+    pub fn finish(self) -> Result<OImage<F>, BuildError> {
+        if self.height == 0 {
+            return Err(BuildError::ZeroHeight);
+        }
+        if self.rows != self.height {
+            return Err(BuildError::TooFewRows {
+                expected: self.height,
+                actual: self.rows,
+            });
+        }
+        Ok(OImage::from_parts(
+            self.width,
+            self.height,
+            self.stride,
+            self.buf,
+        ))
+    }
+}
+
+impl<F: PixelFormat> std::fmt::Debug for OImageBuilder<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("OImageBuilder")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("stride", &self.stride)
+            .field("rows", &self.rows)
+            .finish_non_exhaustive()
+    }
+}
+
+// -----
+
+/// An owned YUV 4:2:0 image with separate Y, U and V planes.
+///
+/// This is the [I420](crate::pixel_format::I420) layout. The U and V planes
+/// are subsampled 2x2 so have dimensions `ceil(width/2)` x `ceil(height/2)`.
+/// Each plane has its own stride.
+#[derive(Clone)]
+pub struct PlanarImage {
+    y: OImage<Mono8>,
+    u: OImage<Mono8>,
+    v: OImage<Mono8>,
+}
+
+impl PlanarImage {
+    /// Combine Y, U and V planes into a planar image.
+    ///
+    /// Returns None if the U and V planes do not have the subsampled
+    /// dimensions of the Y plane.
+    pub fn new(y: OImage<Mono8>, u: OImage<Mono8>, v: OImage<Mono8>) -> Option<Self> {
+        let chroma_width = (y.width() + 1) / 2;
+        let chroma_height = (y.height() + 1) / 2;
+        for plane in [&u, &v].iter() {
+            if plane.width() != chroma_width || plane.height() != chroma_height {
+                return None;
+            }
+        }
+        Some(Self { y, u, v })
+    }
+
+    /// Number of pixel columns in the image.
+    pub fn width(&self) -> u32 {
+        self.y.width()
+    }
+
+    /// Number of pixel rows in the image.
+    pub fn height(&self) -> u32 {
+        self.y.height()
+    }
+
+    /// A view of the full resolution luma plane.
+    pub fn y_plane(&self) -> ImageRef<'_, Mono8> {
+        plane_ref(&self.y)
+    }
+
+    /// A view of the subsampled U (Cb) chroma plane.
+    pub fn u_plane(&self) -> ImageRef<'_, Mono8> {
+        plane_ref(&self.u)
+    }
+
+    /// A view of the subsampled V (Cr) chroma plane.
+    pub fn v_plane(&self) -> ImageRef<'_, Mono8> {
+        plane_ref(&self.v)
+    }
+
+    /// Convert to a tightly packed RGB8 image.
+    ///
+    /// This uses the BT.601 limited range ("video range") coefficients
+    /// commonly used by H.264 and H.265 streams.
+    pub fn i420_to_rgb8(&self) -> OImage<RGB8> {
+        let width = self.width();
+        let height = self.height();
+        let stride = width as usize * 3;
+        let mut buf = Vec::with_capacity(stride * height as usize);
+        let u_rows: Vec<&[u8]> = self.u.rowchunks_exact().collect();
+        let v_rows: Vec<&[u8]> = self.v.rowchunks_exact().collect();
+        let clamp = |x: i32| (x >> 8).clamp(0, 255) as u8;
+        for (row_idx, y_row) in self.y.rowchunks_exact().enumerate() {
+            let u_row = u_rows[row_idx / 2];
+            let v_row = v_rows[row_idx / 2];
+            for (col_idx, y) in y_row.iter().enumerate() {
+                let c = *y as i32 - 16;
+                let d = u_row[col_idx / 2] as i32 - 128;
+                let e = v_row[col_idx / 2] as i32 - 128;
+                buf.push(clamp(298 * c + 409 * e + 128));
+                buf.push(clamp(298 * c - 100 * d - 208 * e + 128));
+                buf.push(clamp(298 * c + 516 * d + 128));
+            }
+        }
+        OImage::from_parts(width, height, stride, buf)
+    }
+}
+
+impl std::fmt::Debug for PlanarImage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("PlanarImage")
+            .field("width", &self.width())
+            .field("height", &self.height())
+            .finish_non_exhaustive()
+    }
+}
+
+fn plane_ref(plane: &OImage<Mono8>) -> ImageRef<'_, Mono8> {
+    ImageRef::new(
+        plane.width(),
+        plane.height(),
+        plane.stride(),
+        plane.image_data(),
+    )
+    .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{image_ref::ImageRef, pixel_format::Mono8, ImageStride};
+
+    #[test]
+    fn test_eq_hash_ignore_padding() {
+        use std::{
+            collections::{hash_map::DefaultHasher, HashSet},
+            hash::{Hash, Hasher},
+        };
+
+        fn hash_of<T: Hash>(x: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            x.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let packed = OImage::<Mono8>::new(2, 2, 2, vec![1, 2, 3, 4]).unwrap();
+        let padded = OImage::<Mono8>::new(2, 2, 4, vec![1, 2, 99, 99, 3, 4]).unwrap();
+        let other = OImage::<Mono8>::new(2, 2, 2, vec![1, 2, 3, 5]).unwrap();
+
+        assert_eq!(packed, padded);
+        assert_eq!(hash_of(&packed), hash_of(&padded));
+        assert_ne!(packed, other);
+
+        let set: HashSet<_> = vec![packed, padded, other].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_try_reshape() {
+        let im = OImage::<Mono8>::new(4, 3, 4, (0..12).collect()).unwrap();
+
+        let im = im.try_reshape(6, 2, 6).unwrap();
+        assert_eq!((im.width(), im.height(), im.stride()), (6, 2, 6));
+        assert_eq!(im.image_data(), &(0..12).collect::<Vec<u8>>()[..]);
+
+        let (im, err) = im.try_reshape(5, 3, 5).unwrap_err();
+        assert_eq!(
+            err,
+            BufferError::TooSmall {
+                required: 15,
+                actual: 12
+            }
+        );
+        // The original image is returned unchanged.
+        assert_eq!((im.width(), im.height(), im.stride()), (6, 2, 6));
+
+        let (_im, err) = im.try_reshape(1, 0, 1).unwrap_err();
+        assert_eq!(err, BufferError::ZeroHeight);
+    }
+
+    #[test]
+    fn test_into_iter_rows() {
+        let im = OImage::<Mono8>::new(2, 3, 3, vec![1, 2, 0, 3, 4, 0, 5, 6]).unwrap();
+        let mut rows = Vec::new();
+        for row in &im {
+            rows.push(row);
+        }
+        assert_eq!(rows, vec![&[1, 2][..], &[3, 4][..], &[5, 6][..]]);
+    }
+
+    #[test]
+    fn test_i420_to_rgb8() {
+        // Solid BT.601 red in a frame with odd dimensions and padded planes.
+        let y = OImage::<Mono8>::new(3, 3, 4, vec![81; 12]).unwrap();
+        let u = OImage::<Mono8>::new(2, 2, 2, vec![90; 4]).unwrap();
+        let v = OImage::<Mono8>::new(2, 2, 3, vec![240; 6]).unwrap();
+        let im = PlanarImage::new(y, u, v).unwrap();
+        assert_eq!(im.y_plane().stride(), 4);
+        assert_eq!(im.v_plane().width(), 2);
+
+        let rgb = im.i420_to_rgb8();
+        assert_eq!((rgb.width(), rgb.height(), rgb.stride()), (3, 3, 9));
+        for px in rgb.image_data().chunks_exact(3) {
+            assert_eq!(px, &[255, 0, 0]);
+        }
+
+        let y = OImage::<Mono8>::new(4, 2, 4, vec![16; 8]).unwrap();
+        let u = OImage::<Mono8>::new(1, 1, 1, vec![128]).unwrap();
+        let v = OImage::<Mono8>::new(1, 1, 1, vec![128]).unwrap();
+        assert!(PlanarImage::new(y, u, v).is_none());
+    }
+
+    #[test]
+    fn test_pad_to_stride() {
+        let im = OImage::<Mono8>::new(10, 3, 10, (0..30).collect()).unwrap();
+        assert_eq!(im.align_stride_to(0), None);
+        assert_eq!(im.align_stride_to(5), Some(10));
+        let stride = im.align_stride_to(64).unwrap();
+        assert_eq!(stride, 64);
+
+        let padded = im.pad_to_stride(stride).unwrap();
+        assert_eq!((padded.width(), padded.height()), (10, 3));
+        assert_eq!(padded.stride(), 64);
+        assert_eq!(padded.image_data().len(), 64 * 3);
+        for (row_idx, row) in padded.image_data().chunks_exact(64).enumerate() {
+            let start = row_idx as u8 * 10;
+            assert_eq!(&row[..10], &(start..start + 10).collect::<Vec<u8>>()[..]);
+            assert!(row[10..].iter().all(|x| *x == 0));
+        }
+        assert_eq!(padded, im);
+
+        assert!(im.pad_to_stride(9).is_none());
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let mut im = OImage::<RGB8>::with_capacity(2, 3, 8).unwrap();
+        assert_eq!(im.image_data().len(), 22);
+        assert_eq!(
+            im.image_data().len(),
+            OImage::<RGB8>::zeros(2, 3, 8).unwrap().image_data().len()
+        );
+        assert!(im.image_data().iter().all(|x| *x == 0));
+        for (y, row) in im.rowchunks_exact_mut().enumerate() {
+            row.fill(y as u8 + 1);
+        }
+        assert_eq!(im.rowchunks_exact().nth(2), Some(&[3; 6][..]));
+
+        // Reuse the buffer for a frame of a different size.
+        let ptr = im.image_data().as_ptr();
+        let im = im.try_reshape(3, 2, 12).unwrap();
+        assert_eq!(im.image_data().as_ptr(), ptr);
+
+        assert!(OImage::<RGB8>::with_capacity(2, 0, 8).is_none());
+        assert!(OImage::<RGB8>::with_capacity(2, 3, 5).is_none());
+        assert!(OImage::<RGB8>::with_capacity(1, u32::MAX, usize::MAX / 2).is_none());
+    }
+
+    #[test]
+    fn test_pixel_format() {
+        use crate::PixFmt;
+        let im = OImage::<RGB8>::zeros(2, 2, 6).unwrap();
+        assert_eq!(im.pixel_format(), PixFmt::RGB8);
+        let erased: &dyn ImageData<RGB8> = &im;
+        assert_eq!(erased.try_pixel_format(), Some(PixFmt::RGB8));
+    }
+
+    #[test]
+    fn test_try_from_tuple() {
+        use std::convert::TryInto;
+
+        let im: OImage<Mono8> = (3, 2, 4, vec![0u8; 7]).try_into().unwrap();
+        assert_eq!((im.width(), im.height(), im.stride()), (3, 2, 4));
+
+        let result: Result<OImage<Mono8>, _> = (3, 2, 4, vec![0u8; 6]).try_into();
+        assert_eq!(
+            result.unwrap_err(),
+            BufferError::TooSmall {
+                required: 7,
+                actual: 6
+            }
+        );
+    }
+
+    #[test]
+    fn test_cloneable_owned_image() {
+        use crate::CloneableOwnedImage;
+
+        fn clone_generic<F, T: CloneableOwnedImage<F>>(im: &T) -> T {
+            im.clone()
+        }
+
+        let im = OImage::<Mono8>::new(2, 1, 2, vec![1, 2]).unwrap();
+        let cloned = clone_generic(&im);
+        assert_eq!(cloned, im);
+
+        let boxed = im.boxed_clone();
+        assert_eq!(boxed.width(), 2);
+        assert_eq!(boxed.image_data(), &[1, 2]);
+    }
+
+    #[test]
+    fn test_mvf_roundtrip() {
+        let im = OImage::<RGB8>::new(2, 2, 8, (0..14).collect()).unwrap();
+        let mut file = Vec::new();
+        im.write_mvf(&mut file).unwrap();
+        assert_eq!(&file[..4], b"MVF1");
+
+        let mut cursor = std::io::Cursor::new(&file);
+        let im2 = OImage::<RGB8>::read_mvf(&mut cursor).unwrap();
+        assert_eq!((im2.width(), im2.height(), im2.stride()), (2, 2, 8));
+        assert_eq!(im2.image_data(), im.image_data());
+
+        let err = OImage::<Mono8>::read_mvf(&mut std::io::Cursor::new(&file)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        let truncated = &file[..file.len() - 1];
+        assert!(OImage::<RGB8>::read_mvf(&mut std::io::Cursor::new(truncated)).is_err());
+
+        let mut bad_magic = file.clone();
+        bad_magic[0] = b'X';
+        let err = OImage::<RGB8>::read_mvf(&mut std::io::Cursor::new(bad_magic)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_mvf_malicious_header() {
+        fn header(height: u32, stride: u64, len: u64) -> Vec<u8> {
+            let mut file = b"MVF1".to_vec();
+            file.push(4);
+            file.extend_from_slice(b"RGB8");
+            file.extend_from_slice(&1u32.to_le_bytes());
+            file.extend_from_slice(&height.to_le_bytes());
+            file.extend_from_slice(&stride.to_le_bytes());
+            file.extend_from_slice(&len.to_le_bytes());
+            file
+        }
+
+        // `stride * (height - 1)` overflows.
+        let file = header(4, u64::MAX / 2, u64::MAX);
+        let err = OImage::<RGB8>::read_mvf(&mut std::io::Cursor::new(file)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        // A huge claimed length with no data must not be allocated up front.
+        let file = header(1, 3, 1 << 46);
+        let err = OImage::<RGB8>::read_mvf(&mut std::io::Cursor::new(file)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_raw_roundtrip() {
+        let im = OImage::<RGB8>::new(2, 2, 8, (0..14).collect()).unwrap();
+        let path = std::env::temp_dir().join(format!("mvf-raw-{}.rgb8", std::process::id()));
+        im.save_raw(&path).unwrap();
+        let name = path.to_str().unwrap();
+        assert_eq!(
+            crate::PixFmt::guess_from_extension(name),
+            Some(crate::PixFmt::RGB8)
+        );
+        let im2 = OImage::<RGB8>::load_raw(&path, 2, 2, 8).unwrap();
+        assert_eq!(im2, im);
+        assert_eq!(im2.image_data(), im.image_data());
+
+        let err = OImage::<RGB8>::load_raw(&path, 2, 3, 8).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_move_to_thread() {
+        let im = OImage::<Mono8>::new(2, 1, 2, vec![1, 2]).unwrap();
+        let handle = std::thread::spawn(move || im.image_data().iter().sum::<u8>());
+        assert_eq!(handle.join().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_from_dyn_image_stride() {
+        let buf = [1, 2, 0, 3, 4, 0];
+        let im = ImageRef::<Mono8>::new(2, 2, 3, &buf).unwrap();
+        let erased: &dyn ImageStride<Mono8> = &im;
+        let owned = OImage::from(erased);
+        assert_eq!(owned.width(), 2);
+        assert_eq!(owned.height(), 2);
+        assert_eq!(owned.stride(), 3);
+        assert_eq!(owned.image_data(), &buf[..]);
+    }
+
+    #[test]
+    fn test_from_image_ref() {
+        let mut buf = [1, 2, 0, 3, 4, 0];
+        let im = ImageRef::<Mono8>::new(2, 2, 3, &buf).unwrap();
+        let owned: OImage<Mono8> = im.into();
+        assert_eq!((owned.width(), owned.height(), owned.stride()), (2, 2, 3));
+        assert_eq!(owned.image_data(), &buf[..]);
+
+        let im = ImageRefMut::<Mono8>::new(2, 2, 3, &mut buf).unwrap();
+        let owned: OImage<Mono8> = im.into();
+        assert_eq!((owned.width(), owned.height(), owned.stride()), (2, 2, 3));
+        assert_eq!(owned.image_data(), &[1, 2, 0, 3, 4, 0]);
+    }
+
+    #[test]
+    fn test_append_rows() {
+        // The initial buffer lacks the padding after its last row.
+        let mut im = OImage::<Mono8>::new(2, 1, 3, vec![1, 2]).unwrap();
+        im.append_rows(&[3, 4, 0, 5, 6, 0], 2).unwrap();
+        im.append_rows(&[7, 8, 0], 1).unwrap();
+        assert_eq!(im.height(), 4);
+        assert_eq!(im.image_data(), &[1, 2, 0, 3, 4, 0, 5, 6, 0, 7, 8, 0]);
+        let rows: Vec<&[u8]> = im.rowchunks_exact().collect();
+        assert_eq!(rows, vec![&[1, 2][..], &[3, 4], &[5, 6], &[7, 8]]);
+
+        assert_eq!(
+            im.append_rows(&[9, 9], 1),
+            Err(AppendError::Length {
+                expected: 3,
+                actual: 2
+            })
+        );
+        assert_eq!(im.height(), 4);
+    }
+
+    #[test]
+    fn test_gradient() {
+        let im = OImage::<Mono8>::gradient(256, 2).unwrap();
+        assert_eq!((im.width(), im.height(), im.stride()), (256, 2, 256));
+        let row = im.rowchunks_exact().nth(1).unwrap();
+        assert_eq!((row[0], row[128], row[255]), (0, 128, 255));
+
+        let im = OImage::<Mono8>::gradient(3, 1).unwrap();
+        assert_eq!(im.image_data(), &[0, 127, 255]);
+        assert_eq!(OImage::<Mono8>::gradient(1, 1).unwrap().image_data(), &[0]);
+        assert!(OImage::<Mono8>::gradient(3, 0).is_none());
+    }
+
+    #[test]
+    fn test_color_bars() {
+        let im = OImage::<RGB8>::color_bars(14, 2).unwrap();
+        assert_eq!((im.width(), im.height(), im.stride()), (14, 2, 42));
+        let row = im.rowchunks_exact().nth(1).unwrap();
+        assert_eq!(&row[..3], &[191, 191, 191]);
+        assert_eq!(&row[6..9], &[191, 191, 0]);
+        assert_eq!(&row[39..], &[0, 0, 191]);
+        assert!(OImage::<RGB8>::color_bars(14, 0).is_none());
+    }
+
+    #[test]
+    fn test_resize_nearest() {
+        let im = OImage::<RGB8>::new(2, 2, 7, (0..13).collect()).unwrap();
+        let big = im.resize_nearest(4, 4).unwrap();
+        assert_eq!((big.width(), big.height(), big.stride()), (4, 4, 12));
+        let expected_row0 = [0, 1, 2, 0, 1, 2, 3, 4, 5, 3, 4, 5];
+        assert_eq!(&big.image_data()[..12], &expected_row0);
+        assert_eq!(&big.image_data()[36..39], &[7, 8, 9]);
+
+        let small = big.resize_nearest(1, 1).unwrap();
+        assert_eq!(small.image_data(), &[0, 1, 2]);
+
+        assert!(im.resize_nearest(0, 3).is_none());
+        assert!(im.resize_nearest(3, 0).is_none());
+    }
+
+    #[test]
+    fn test_resize_bilinear() {
+        let im = OImage::<Mono8>::new(2, 2, 2, vec![0, 100, 200, 40]).unwrap();
+        let big = im.resize_bilinear(4, 4).unwrap();
+        assert_eq!((big.width(), big.height(), big.stride()), (4, 4, 4));
+        let data = big.image_data();
+        // Corners are unchanged.
+        assert_eq!((data[0], data[3], data[12], data[15]), (0, 100, 200, 40));
+        // Along the top edge, x=1 maps to source x=0.25.
+        assert_eq!(data[1], 25);
+        // The pixel at (1, 1) maps to source (0.25, 0.25).
+        let expected = 0.0 * 0.5625 + 100.0 * 0.1875 + 200.0 * 0.1875 + 40.0 * 0.0625;
+        assert_eq!(data[5], (expected + 0.5f32) as u8);
+
+        let rgb = OImage::<RGB8>::new(1, 1, 3, vec![10, 20, 30]).unwrap();
+        let rgb_big = rgb.resize_bilinear(2, 1).unwrap();
+        assert_eq!(rgb_big.image_data(), &[10, 20, 30, 10, 20, 30]);
+
+        assert!(im.resize_bilinear(0, 0).is_none());
+        assert!(im.resize_bilinear(2, 0).is_none());
+        assert!(rgb.resize_bilinear(0, 1).is_none());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mmap_image() {
+        use crate::iter::HasRow;
+        use std::io::Write;
+
+        let path = std::env::temp_dir().join(format!("mvf-mmap-{}.raw", std::process::id()));
+        {
+            let mut file = std::fs::File::create(&path).unwrap();
+            file.write_all(&[1, 2, 3, 0, 4, 5, 6]).unwrap();
+        }
+        let file = std::fs::File::open(&path).unwrap();
+        // SAFETY: the file is private to this test and not modified while
+        // mapped.
+        let mmap = unsafe { memmap2::Mmap::map(&file).unwrap() };
+        let im = MmapImage::<Mono8>::new(3, 2, 4, mmap).unwrap();
+        assert_eq!(im.row(1), Some(&[4, 5, 6][..]));
+        assert_eq!(OImage::copy_from(&im).image_data(), &[1, 2, 3, 0, 4, 5, 6]);
+
+        // SAFETY: as above.
+        let mmap = unsafe { memmap2::Mmap::map(&file).unwrap() };
+        assert!(MmapImage::<Mono8>::new(3, 3, 4, mmap).is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_reinterpret() {
+        use crate::pixel_format::{BayerRG8, Mono16, Mono16Le, RGBA8};
+
+        let mono = OImage::<Mono8>::new(2, 2, 3, vec![1, 2, 0, 3, 4]).unwrap();
+        let bayer = mono.reinterpret::<BayerRG8>().unwrap();
+        assert_eq!((bayer.width(), bayer.height(), bayer.stride()), (2, 2, 3));
+        assert_eq!(bayer.image_data(), &[1, 2, 0, 3, 4]);
+
+        let le = OImage::<Mono16>::new(1, 1, 2, vec![1, 2])
+            .unwrap()
+            .reinterpret::<Mono16Le>()
+            .unwrap();
+        assert_eq!(le.image_data(), &[1, 2]);
+
+        let rgb = OImage::<RGB8>::new(2, 2, 6, vec![0; 12]).unwrap();
+        let (rgb, e) = rgb.reinterpret::<RGBA8>().unwrap_err();
+        assert_eq!(
+            e,
+            BufferError::StrideTooSmall {
+                required: 8,
+                actual: 6
+            }
+        );
+        assert_eq!(rgb.image_data().len(), 12);
+
+        let rgb = OImage::<RGB8>::new(2, 2, 8, vec![0; 14]).unwrap();
+        let (_, e) = rgb.reinterpret::<RGBA8>().unwrap_err();
+        assert_eq!(
+            e,
+            BufferError::TooSmall {
+                required: 16,
+                actual: 14
+            }
+        );
+    }
+
+    #[test]
+    fn test_new_unchecked() {
+        let buf = vec![1, 2, 0, 3, 4];
+        let checked = OImage::<Mono8>::new(2, 2, 3, buf.clone()).unwrap();
+        // SAFETY: the height is nonzero and the buffer holds 3 * (2 - 1) + 2
+        // bytes.
+        let unchecked = unsafe { OImage::<Mono8>::new_unchecked(2, 2, 3, buf) };
+        assert_eq!(unchecked.stride(), checked.stride());
+        assert_eq!(unchecked.image_data(), checked.image_data());
+        assert_eq!(unchecked, checked);
+    }
+
+    #[test]
+    fn test_copy_into_fixed() {
+        let src_buf = [1, 2, 0, 3, 4];
+        let src = ImageRef::<Mono8>::new(2, 2, 3, &src_buf).unwrap();
+        let mut dst = OImage::<Mono8>::new(4, 4, 4, vec![9; 16]).unwrap();
+        copy_into_fixed(&src, &mut dst).unwrap();
+        #[rustfmt::skip]
+        let expected = [
+            1, 2, 9, 9,
+            3, 4, 9, 9,
+            9, 9, 9, 9,
+            9, 9, 9, 9,
+        ];
+        assert_eq!(dst.image_data(), &expected);
+
+        let mut small = OImage::<Mono8>::zeros(1, 4, 1).unwrap();
+        assert_eq!(
+            copy_into_fixed(&src, &mut small),
+            Err(CopyError::DoesNotFit)
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let im = OImage::<RGB8>::zeros(2, 3, 8).unwrap();
+        assert_eq!(format!("{}", im), "RGB8 2x3 stride=8");
+    }
+
+    #[test]
+    fn test_builder() {
+        let mut builder = OImageBuilder::<Mono8>::new(2, 3, 3);
+        builder.push_row(&[1, 2]).unwrap();
+        assert_eq!(
+            builder.push_row(&[3]),
+            Err(BuildError::RowLength {
+                expected: 2,
+                actual: 1
+            })
+        );
+        builder.push_row(&[3, 4]).unwrap();
+        builder.push_row(&[5, 6]).unwrap();
+        assert_eq!(builder.push_row(&[7, 8]), Err(BuildError::TooManyRows));
+        let im = builder.finish().unwrap();
+        assert_eq!((im.width(), im.height(), im.stride()), (2, 3, 3));
+        assert_eq!(im.image_data(), &[1, 2, 0, 3, 4, 0, 5, 6]);
+
+        let mut builder = OImageBuilder::<Mono8>::new(2, 3, 2);
+        builder.push_row(&[1, 2]).unwrap();
+        assert_eq!(
+            builder.finish().unwrap_err(),
+            BuildError::TooFewRows {
+                expected: 3,
+                actual: 1
+            }
+        );
+
+        let mut builder = OImageBuilder::<Mono8>::new(2, 3, 1);
+        assert_eq!(builder.push_row(&[1, 2]), Err(BuildError::StrideTooSmall));
+    }
+}
@@ -15,11 +15,16 @@ extern crate core as std;
 
 use std::convert::TryFrom;
 
+pub use crate::error::PixFmtError;
+
 /// This type allows runtime inspection of pixel format.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[non_exhaustive]
 pub enum PixFmt {
     Mono8,
+    Mono16,
+    Mono16Le,
+    Mono16Be,
     Mono32f,
     RGB8,
     RGBA8,
@@ -34,9 +39,63 @@ pub enum PixFmt {
     YUV444,
     YUV422,
     NV12,
+    I420,
+    Coord3D_ABC32f,
+}
+
+/// The numeric type of the samples of a pixel format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SampleType {
+    /// Unsigned 8-bit integer.
+    U8,
+    /// Unsigned 16-bit integer. The byte order depends on the pixel format.
+    U16,
+    /// 32-bit IEEE-754 floating point.
+    F32,
 }
 
 impl PixFmt {
+    /// All pixel formats defined in this crate.
+    ///
+    /// Because [PixFmt] is `#[non_exhaustive]`, this allows iterating over the
+    /// supported formats without an exhaustive `match`.
+    pub const fn all() -> &'static [PixFmt] {
+        use PixFmt::*;
+        &[
+            Mono8,
+            Mono16,
+            Mono16Le,
+            Mono16Be,
+            Mono32f,
+            RGB8,
+            RGBA8,
+            BayerRG8,
+            BayerRG32f,
+            BayerBG8,
+            BayerBG32f,
+            BayerGB8,
+            BayerGB32f,
+            BayerGR8,
+            BayerGR32f,
+            YUV444,
+            YUV422,
+            NV12,
+            I420,
+            Coord3D_ABC32f,
+        ]
+    }
+    /// Guess the pixel format from the extension of a file name.
+    ///
+    /// The extension is the format name, compared case-insensitively, so
+    /// `frame.mono8` gives `Mono8` and `frame.RGB8` gives `RGB8`.
+    pub fn guess_from_extension(path: &str) -> Option<PixFmt> {
+        let (_, ext) = path.rsplit_once('.')?;
+        PixFmt::all()
+            .iter()
+            .find(|fmt| fmt.as_str().eq_ignore_ascii_case(ext))
+            .copied()
+    }
     /// Convert a runtime variant into a static type.
     pub fn to_static<FMT: PixelFormat>(&self) -> Option<std::marker::PhantomData<FMT>> {
         let other = pixfmt::<FMT>();
@@ -46,11 +105,21 @@ impl PixFmt {
             None
         }
     }
+    /// Whether this runtime variant is the static type `FMT`.
+    ///
+    /// This allows branching on the pixel format in generic code. It is false
+    /// if `FMT` is not one of the pixel formats defined in this crate.
+    pub fn matches<FMT: PixelFormat>(&self) -> bool {
+        self.to_static::<FMT>().is_some()
+    }
     /// The average number of bits per pixel.
     pub const fn bits_per_pixel(&self) -> u8 {
         use PixFmt::*;
         match self {
             Mono8 => 8,
+            Mono16 => 16,
+            Mono16Le => 16,
+            Mono16Be => 16,
             Mono32f => 32,
             RGB8 => 24,
             RGBA8 => 32,
@@ -65,6 +134,89 @@ impl PixFmt {
             YUV444 => 24,
             YUV422 => 16,
             NV12 => 12,
+            I420 => 12,
+            Coord3D_ABC32f => 96,
+        }
+    }
+    /// The number of significant bits in each sample.
+    ///
+    /// No pixel format in this crate stores samples in a partially used
+    /// container (such as 10 or 12 bits in 16), so this always equals the
+    /// width of a stored sample, e.g. 8 for `RGB8` and 16 for `Mono16`. For
+    /// floating point formats it is the size of the float.
+    pub const fn effective_bit_depth(&self) -> u8 {
+        use PixFmt::*;
+        match self {
+            Mono8 | RGB8 | RGBA8 | BayerRG8 | BayerBG8 | BayerGB8 | BayerGR8 => 8,
+            YUV444 | YUV422 | NV12 | I420 => 8,
+            Mono16 | Mono16Le | Mono16Be => 16,
+            Mono32f | BayerRG32f | BayerBG32f | BayerGB32f | BayerGR32f => 32,
+            Coord3D_ABC32f => 32,
+        }
+    }
+    /// The numeric type of each sample.
+    pub const fn sample_type(&self) -> SampleType {
+        use PixFmt::*;
+        match self {
+            Mono8 | RGB8 | RGBA8 | BayerRG8 | BayerBG8 | BayerGB8 | BayerGR8 => SampleType::U8,
+            YUV444 | YUV422 | NV12 | I420 => SampleType::U8,
+            Mono16 | Mono16Le | Mono16Be => SampleType::U16,
+            Mono32f | BayerRG32f | BayerBG32f | BayerGB32f | BayerGR32f => SampleType::F32,
+            Coord3D_ABC32f => SampleType::F32,
+        }
+    }
+    /// The number of channels per pixel.
+    ///
+    /// Bayer formats are raw mosaic data with a single channel per pixel, even
+    /// though they encode color (see [PixFmt::is_color]). YUV formats count as
+    /// three channels regardless of chroma subsampling.
+    pub const fn channels(&self) -> u8 {
+        use PixFmt::*;
+        match self {
+            Mono8 | Mono16 | Mono16Le | Mono16Be | Mono32f => 1,
+            RGB8 => 3,
+            RGBA8 => 4,
+            BayerRG8 | BayerRG32f | BayerBG8 | BayerBG32f | BayerGB8 | BayerGB32f | BayerGR8
+            | BayerGR32f => 1,
+            YUV444 | YUV422 | NV12 | I420 => 3,
+            Coord3D_ABC32f => 3,
+        }
+    }
+    /// Whether the pixel format encodes color.
+    ///
+    /// This is true for Bayer formats, which encode color in the mosaic
+    /// pattern, and false for 3D coordinate formats.
+    pub const fn is_color(&self) -> bool {
+        use PixFmt::*;
+        !matches!(
+            self,
+            Mono8 | Mono16 | Mono16Le | Mono16Be | Mono32f | Coord3D_ABC32f
+        )
+    }
+    /// A short, human-readable description of the pixel format.
+    pub const fn describe(&self) -> &'static str {
+        use PixFmt::*;
+        match self {
+            Mono8 => "Luminance, 1 byte per pixel",
+            Mono16 => "Luminance, 2 bytes per pixel, little-endian",
+            Mono16Le => "Luminance, 2 bytes per pixel, little-endian",
+            Mono16Be => "Luminance, 2 bytes per pixel, big-endian",
+            Mono32f => "Luminance, 4 bytes per pixel, IEEE-754 float",
+            RGB8 => "Red, green, blue, 1 byte each",
+            RGBA8 => "Red, green, blue, alpha, 1 byte each",
+            BayerRG8 => "Bayer red green pattern, 1 byte per pixel",
+            BayerRG32f => "Bayer red green pattern, 4 bytes per pixel",
+            BayerBG8 => "Bayer blue green pattern, 1 byte per pixel",
+            BayerBG32f => "Bayer blue green pattern, 4 bytes per pixel",
+            BayerGB8 => "Bayer green blue pattern, 1 byte per pixel",
+            BayerGB32f => "Bayer green blue pattern, 4 bytes per pixel",
+            BayerGR8 => "Bayer green red pattern, 1 byte per pixel",
+            BayerGR32f => "Bayer green red pattern, 4 bytes per pixel",
+            YUV444 => "YUV 4:4:4, 8 bits per sample",
+            YUV422 => "YUV 4:2:2, 8 bits per sample",
+            NV12 => "YUV 4:2:0 with interleaved chroma plane",
+            I420 => "YUV 4:2:0 with separate Y, U and V planes",
+            Coord3D_ABC32f => "3D coordinates, three 4-byte IEEE-754 floats per pixel",
         }
     }
     /// The name of the pixel format.
@@ -72,6 +224,9 @@ impl PixFmt {
         use PixFmt::*;
         match self {
             Mono8 => "Mono8",
+            Mono16 => "Mono16",
+            Mono16Le => "Mono16Le",
+            Mono16Be => "Mono16Be",
             Mono32f => "Mono32f",
             RGB8 => "RGB8",
             RGBA8 => "RGBA8",
@@ -86,6 +241,66 @@ impl PixFmt {
             YUV444 => "YUV444",
             YUV422 => "YUV422",
             NV12 => "NV12",
+            I420 => "I420",
+            Coord3D_ABC32f => "Coord3D_ABC32f",
+        }
+    }
+    /// A stable numeric identifier of the pixel format.
+    ///
+    /// Unlike the enum discriminant, the identifier of a format never changes,
+    /// so it is suitable for serialization. Identifiers are not reused.
+    pub const fn numeric_id(&self) -> u32 {
+        use PixFmt::*;
+        match self {
+            Mono8 => 1,
+            Mono16 => 2,
+            Mono16Le => 3,
+            Mono16Be => 4,
+            Mono32f => 5,
+            RGB8 => 6,
+            RGBA8 => 7,
+            BayerRG8 => 8,
+            BayerRG32f => 9,
+            BayerBG8 => 10,
+            BayerBG32f => 11,
+            BayerGB8 => 12,
+            BayerGB32f => 13,
+            BayerGR8 => 14,
+            BayerGR32f => 15,
+            YUV444 => 16,
+            YUV422 => 17,
+            NV12 => 18,
+            I420 => 19,
+            Coord3D_ABC32f => 20,
+        }
+    }
+    /// The pixel format with the given [PixFmt::numeric_id].
+    ///
+    /// Returns None if no format has this identifier.
+    pub const fn from_numeric_id(id: u32) -> Option<PixFmt> {
+        use PixFmt::*;
+        match id {
+            1 => Some(Mono8),
+            2 => Some(Mono16),
+            3 => Some(Mono16Le),
+            4 => Some(Mono16Be),
+            5 => Some(Mono32f),
+            6 => Some(RGB8),
+            7 => Some(RGBA8),
+            8 => Some(BayerRG8),
+            9 => Some(BayerRG32f),
+            10 => Some(BayerBG8),
+            11 => Some(BayerBG32f),
+            12 => Some(BayerGB8),
+            13 => Some(BayerGB32f),
+            14 => Some(BayerGR8),
+            15 => Some(BayerGR32f),
+            16 => Some(YUV444),
+            17 => Some(YUV422),
+            18 => Some(NV12),
+            19 => Some(I420),
+            20 => Some(Coord3D_ABC32f),
+            _ => None,
         }
     }
 }
@@ -96,12 +311,32 @@ impl std::fmt::Display for PixFmt {
     }
 }
 
+/// Formats are ordered by [PixFmt::numeric_id].
+impl PartialOrd for PixFmt {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Formats are ordered by [PixFmt::numeric_id].
+impl Ord for PixFmt {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.numeric_id().cmp(&other.numeric_id())
+    }
+}
+
 impl std::str::FromStr for PixFmt {
     type Err = &'static str;
     fn from_str(instr: &str) -> Result<Self, <Self as std::str::FromStr>::Err> {
         use PixFmt::*;
         if instr == "Mono8" {
             Ok(Mono8)
+        } else if instr == "Mono16" {
+            Ok(Mono16)
+        } else if instr == "Mono16Le" {
+            Ok(Mono16Le)
+        } else if instr == "Mono16Be" {
+            Ok(Mono16Be)
         } else if instr == "Mono32f" {
             Ok(Mono32f)
         } else if instr == "RGB8" {
@@ -130,6 +365,10 @@ impl std::str::FromStr for PixFmt {
             Ok(YUV422)
         } else if instr == "NV12" {
             Ok(NV12)
+        } else if instr == "I420" {
+            Ok(I420)
+        } else if instr == "Coord3D_ABC32f" {
+            Ok(Coord3D_ABC32f)
         } else {
             Err("Cannot parse string")
         }
@@ -140,8 +379,26 @@ impl std::str::FromStr for PixFmt {
 fn test_pixfmt_roundtrip() {
     use PixFmt::*;
     let fmts = [
-        Mono8, Mono32f, RGB8, RGBA8, BayerRG8, BayerRG32f, BayerBG8, BayerBG32f, BayerGB8,
-        BayerGB32f, BayerGR8, BayerGR32f, YUV444, YUV422, NV12,
+        Mono8,
+        Mono16,
+        Mono16Le,
+        Mono16Be,
+        Mono32f,
+        RGB8,
+        RGBA8,
+        BayerRG8,
+        BayerRG32f,
+        BayerBG8,
+        BayerBG32f,
+        BayerGB8,
+        BayerGB32f,
+        BayerGR8,
+        BayerGR32f,
+        YUV444,
+        YUV422,
+        NV12,
+        I420,
+        Coord3D_ABC32f,
     ];
     for fmt in &fmts {
         let fmt_str = fmt.as_str();
@@ -151,6 +408,132 @@ fn test_pixfmt_roundtrip() {
     }
 }
 
+#[test]
+fn test_pixfmt_all() {
+    use PixFmt::*;
+    let all = PixFmt::all();
+    for fmt in &[
+        Mono8,
+        Mono16,
+        Mono16Le,
+        Mono16Be,
+        Mono32f,
+        RGB8,
+        RGBA8,
+        BayerRG8,
+        BayerRG32f,
+        BayerBG8,
+        BayerBG32f,
+        BayerGB8,
+        BayerGB32f,
+        BayerGR8,
+        BayerGR32f,
+        YUV444,
+        YUV422,
+        NV12,
+        I420,
+        Coord3D_ABC32f,
+    ] {
+        assert!(all.contains(fmt), "{}", fmt);
+    }
+    for (i, fmt) in all.iter().enumerate() {
+        assert!(!fmt.describe().is_empty(), "{}", fmt);
+        assert!(!all[i + 1..].contains(fmt), "{} listed twice", fmt);
+        // Every listed format round-trips through its name.
+        assert_eq!(std::str::FromStr::from_str(fmt.as_str()), Ok(*fmt));
+    }
+}
+
+#[test]
+fn test_matches() {
+    assert!(PixFmt::Mono8.matches::<Mono8>());
+    assert!(!PixFmt::Mono8.matches::<RGB8>());
+    // Formats with the same layout are still distinct types.
+    assert!(!PixFmt::Mono16.matches::<Mono16Le>());
+}
+
+#[test]
+fn test_numeric_id() {
+    use PixFmt::*;
+    for fmt in PixFmt::all() {
+        assert_eq!(PixFmt::from_numeric_id(fmt.numeric_id()), Some(*fmt));
+    }
+    // Identifiers are part of the stable serialized form.
+    assert_eq!(Mono8.numeric_id(), 1);
+    assert_eq!(RGB8.numeric_id(), 6);
+    assert_eq!(I420.numeric_id(), 19);
+    assert_eq!(PixFmt::from_numeric_id(0), None);
+    assert_eq!(PixFmt::from_numeric_id(u32::MAX), None);
+
+    let mut fmts = vec![I420, Mono16, RGB8, Mono8];
+    fmts.sort();
+    assert_eq!(fmts, vec![Mono8, Mono16, RGB8, I420]);
+}
+
+#[test]
+fn test_guess_from_extension() {
+    assert_eq!(PixFmt::guess_from_extension("a.mono8"), Some(PixFmt::Mono8));
+    assert_eq!(
+        PixFmt::guess_from_extension("dir.x/a.RGB8"),
+        Some(PixFmt::RGB8)
+    );
+    assert_eq!(
+        PixFmt::guess_from_extension("a.b.bayerrg32f"),
+        Some(PixFmt::BayerRG32f)
+    );
+    assert_eq!(PixFmt::guess_from_extension("a.png"), None);
+    assert_eq!(PixFmt::guess_from_extension("mono8"), None);
+}
+
+#[test]
+fn test_sample_type() {
+    assert_eq!(PixFmt::Mono8.sample_type(), SampleType::U8);
+    assert_eq!(PixFmt::Mono16.sample_type(), SampleType::U16);
+    assert_eq!(PixFmt::Mono16Be.sample_type(), SampleType::U16);
+    assert_eq!(PixFmt::BayerGR32f.sample_type(), SampleType::F32);
+    assert_eq!(PixFmt::NV12.sample_type(), SampleType::U8);
+}
+
+#[test]
+fn test_pixfmt_channels() {
+    use PixFmt::*;
+    let expected = [
+        (Mono8, 1, false),
+        (Mono16, 1, false),
+        (Mono16Le, 1, false),
+        (Mono16Be, 1, false),
+        (Mono32f, 1, false),
+        (RGB8, 3, true),
+        (RGBA8, 4, true),
+        (BayerRG8, 1, true),
+        (BayerRG32f, 1, true),
+        (BayerBG8, 1, true),
+        (BayerBG32f, 1, true),
+        (BayerGB8, 1, true),
+        (BayerGB32f, 1, true),
+        (BayerGR8, 1, true),
+        (BayerGR32f, 1, true),
+        (YUV444, 3, true),
+        (YUV422, 3, true),
+        (NV12, 3, true),
+        (I420, 3, true),
+        (Coord3D_ABC32f, 3, false),
+    ];
+    for (fmt, channels, is_color) in &expected {
+        assert_eq!(fmt.channels(), *channels, "{}", fmt);
+        assert_eq!(fmt.is_color(), *is_color, "{}", fmt);
+    }
+}
+
+#[test]
+fn test_effective_bit_depth() {
+    assert_eq!(PixFmt::Mono8.effective_bit_depth(), 8);
+    assert_eq!(PixFmt::RGB8.effective_bit_depth(), 8);
+    assert_eq!(PixFmt::NV12.effective_bit_depth(), 8);
+    assert_eq!(PixFmt::Mono16Be.effective_bit_depth(), 16);
+    assert_eq!(PixFmt::BayerRG32f.effective_bit_depth(), 32);
+}
+
 macro_rules! try_downcast {
     ($name:ident, $orig:expr) => {{
         if let Some(_) = <dyn std::any::Any>::downcast_ref::<std::marker::PhantomData<$name>>($orig)
@@ -164,10 +547,13 @@ impl<FMT> TryFrom<std::marker::PhantomData<FMT>> for PixFmt
 where
     FMT: PixelFormat,
 {
-    type Error = &'static str;
+    type Error = PixFmtError;
 
     fn try_from(orig: std::marker::PhantomData<FMT>) -> Result<PixFmt, Self::Error> {
         try_downcast!(Mono8, &orig);
+        try_downcast!(Mono16, &orig);
+        try_downcast!(Mono16Le, &orig);
+        try_downcast!(Mono16Be, &orig);
         try_downcast!(Mono32f, &orig);
         try_downcast!(RGB8, &orig);
         try_downcast!(RGBA8, &orig);
@@ -182,18 +568,60 @@ where
         try_downcast!(YUV444, &orig);
         try_downcast!(YUV422, &orig);
         try_downcast!(NV12, &orig);
-        Err("unknown PixelFormat implementation could not be converted to PixFmt")
+        try_downcast!(I420, &orig);
+        try_downcast!(Coord3D_ABC32f, &orig);
+        Err(PixFmtError::Unregistered)
     }
 }
 
+/// Whether image data in format `a` can be used as format `b`.
+///
+/// This is true if the formats are equal or have the same memory layout, such
+/// as `Mono16` and `Mono16Le`.
+pub fn formats_compatible(a: PixFmt, b: PixFmt) -> bool {
+    use PixFmt::*;
+    a == b || matches!((a, b), (Mono16, Mono16Le) | (Mono16Le, Mono16))
+}
+
+#[test]
+fn test_formats_compatible() {
+    use PixFmt::*;
+    assert!(formats_compatible(RGB8, RGB8));
+    assert!(formats_compatible(Mono16, Mono16Le));
+    assert!(formats_compatible(Mono16Le, Mono16));
+    assert!(!formats_compatible(Mono16, Mono16Be));
+    assert!(!formats_compatible(Mono8, BayerRG8));
+    assert!(!formats_compatible(RGB8, YUV444));
+}
+
 /// Convert a compile-time type FMT into a runtime type.
+///
+/// Returns [PixFmtError::Unregistered] if `FMT` is a [PixelFormat]
+/// implementation from outside this crate.
 #[inline]
-pub fn pixfmt<FMT: PixelFormat>() -> Result<PixFmt, &'static str> {
+pub fn pixfmt<FMT: PixelFormat>() -> Result<PixFmt, PixFmtError> {
     use std::convert::TryInto;
     let concrete: std::marker::PhantomData<FMT> = std::marker::PhantomData;
     concrete.try_into()
 }
 
+#[test]
+fn test_unregistered_pixel_format() {
+    #[derive(Clone)]
+    struct Custom {}
+    impl PixelFormat for Custom {
+        const BITS_PER_PIXEL: u8 = 8;
+    }
+
+    assert_eq!(pixfmt::<Custom>(), Err(PixFmtError::Unregistered));
+    assert_eq!(PixFmt::Mono8.to_static::<Custom>(), None);
+
+    // Constructing images does not require a registered format.
+    let buf = [0u8; 4];
+    let im = crate::image_ref::ImageRef::<Custom>::new(2, 2, 2, &buf).unwrap();
+    assert_eq!(crate::Stride::stride(&im), 2);
+}
+
 #[test]
 fn test_compile_runtime_roundtrip() {
     macro_rules! gen_test {
@@ -202,9 +630,13 @@ fn test_compile_runtime_roundtrip() {
             let y = x.to_static::<$name>().unwrap();
             let z = PixFmt::try_from(y).unwrap();
             assert_eq!(x, z);
+            assert_eq!(x.bits_per_pixel(), $name::BITS_PER_PIXEL);
         }};
     }
     gen_test!(Mono8);
+    gen_test!(Mono16);
+    gen_test!(Mono16Le);
+    gen_test!(Mono16Be);
     gen_test!(Mono32f);
     gen_test!(RGB8);
     gen_test!(RGBA8);
@@ -219,6 +651,8 @@ fn test_compile_runtime_roundtrip() {
     gen_test!(YUV444);
     gen_test!(YUV422);
     gen_test!(NV12);
+    gen_test!(I420);
+    gen_test!(Coord3D_ABC32f);
 }
 
 /// Implementations of this trait describe the format of raw image data.
@@ -227,14 +661,21 @@ fn test_compile_runtime_roundtrip() {
 /// types](https://blog.rust-lang.org/2021/02/26/const-generics-mvp-beta.html#const-generics-for-custom-types)
 /// are introduced to the rust compiler, we intend to switch PixelFormat to use
 /// that feature.
-pub trait PixelFormat: std::any::Any + Clone {}
+pub trait PixelFormat: std::any::Any + Clone {
+    /// The average number of bits per pixel.
+    ///
+    /// This is the compile-time equivalent of [PixFmt::bits_per_pixel].
+    const BITS_PER_PIXEL: u8;
+}
 
 macro_rules! define_pixel_format {
     ($name:ident, $comment:literal) => {
         #[doc = $comment]
         #[derive(Clone, Debug)]
         pub struct $name {}
-        impl PixelFormat for $name {}
+        impl PixelFormat for $name {
+            const BITS_PER_PIXEL: u8 = PixFmt::$name.bits_per_pixel();
+        }
     };
 }
 
@@ -242,6 +683,20 @@ define_pixel_format!(
     Mono8,
     "Luminance, 1 byte per pixel. Sometimes also called Gray8."
 );
+define_pixel_format!(Mono16, "Luminance, 2 bytes per pixel, Little-Endian.");
+define_pixel_format!(
+    Mono16Le,
+    "Luminance, 2 bytes per pixel, Little-Endian.
+
+Unlike [Mono16], the byte order is explicitly part of the type. Use the
+functions in the `convert` module to swap to [Mono16Be]."
+);
+define_pixel_format!(
+    Mono16Be,
+    "Luminance, 2 bytes per pixel, Big-Endian.
+
+Use the functions in the `convert` module to swap to [Mono16Le]."
+);
 define_pixel_format!(
     Mono32f,
     "Luminance, 32 bytes per pixel, Little-Endian, IEEE-754"
@@ -270,8 +725,135 @@ define_pixel_format!(BayerGR32f, "Bayer Green Red pattern, 4 bytes per pixel.");
 define_pixel_format!(YUV444, "YUV 4:4:4 8-bit, total 3 bytes per pixel.");
 define_pixel_format!(YUV422, "YUV 4:2:2 8-bit, total 2 bytes per pixel.");
 define_pixel_format!(NV12, "NV12 format, average 12 bits per pixel");
+define_pixel_format!(
+    I420,
+    "YUV 4:2:0 8-bit with separate Y, U and V planes, average 12 bits per pixel.
+
+See `owned::PlanarImage` for a type storing the three planes."
+);
+define_pixel_format!(
+    Coord3D_ABC32f,
+    "3D coordinates (A, B, C), typically X, Y, Z, each a 32-bit little-endian
+float, total 12 bytes per pixel."
+);
+
+// Compile-time test that `BITS_PER_PIXEL` is usable in const contexts.
+const _: [(); 24] = [(); RGB8::BITS_PER_PIXEL as usize];
 
 #[test]
 fn test_debug_types() {
     let _ = format!("{:?}", BayerRG8 {});
 }
+
+/// A pixel format whose pixels can be converted to and from a typed value.
+///
+/// This is separate from [PixelFormat] so that formats without a natural
+/// per-pixel type, such as subsampled YUV formats, need not implement it.
+pub trait PixelCodec: PixelFormat {
+    /// The typed value of a single pixel.
+    type Pixel;
+    /// Decode a pixel from the start of `bytes`.
+    ///
+    /// Panics if `bytes` is shorter than a pixel.
+    fn decode(bytes: &[u8]) -> Self::Pixel;
+    /// Encode a pixel into the start of `out`.
+    ///
+    /// Panics if `out` is shorter than a pixel.
+    fn encode(px: Self::Pixel, out: &mut [u8]);
+}
+
+impl PixelCodec for Mono8 {
+    type Pixel = u8;
+    #[inline]
+    fn decode(bytes: &[u8]) -> u8 {
+        bytes[0]
+    }
+    #[inline]
+    fn encode(px: u8, out: &mut [u8]) {
+        out[0] = px;
+    }
+}
+
+impl PixelCodec for RGB8 {
+    type Pixel = [u8; 3];
+    #[inline]
+    fn decode(bytes: &[u8]) -> [u8; 3] {
+        [bytes[0], bytes[1], bytes[2]]
+    }
+    #[inline]
+    fn encode(px: [u8; 3], out: &mut [u8]) {
+        out[..3].copy_from_slice(&px);
+    }
+}
+
+/// Pixels are little-endian `u16` values.
+impl PixelCodec for Mono16 {
+    type Pixel = u16;
+    #[inline]
+    fn decode(bytes: &[u8]) -> u16 {
+        u16::from_le_bytes([bytes[0], bytes[1]])
+    }
+    #[inline]
+    fn encode(px: u16, out: &mut [u8]) {
+        out[..2].copy_from_slice(&px.to_le_bytes());
+    }
+}
+
+/// Pixels are `[a, b, c]` coordinates stored as little-endian `f32` values.
+impl PixelCodec for Coord3D_ABC32f {
+    type Pixel = [f32; 3];
+    #[inline]
+    fn decode(bytes: &[u8]) -> [f32; 3] {
+        let coord =
+            |i: usize| f32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        [coord(0), coord(4), coord(8)]
+    }
+    #[inline]
+    fn encode(px: [f32; 3], out: &mut [u8]) {
+        for (chunk, coord) in out[..12].chunks_exact_mut(4).zip(px.iter()) {
+            chunk.copy_from_slice(&coord.to_le_bytes());
+        }
+    }
+}
+
+/// The 3D coordinates of the pixel at `(x, y)`.
+///
+/// Returns None if `(x, y)` is outside the image or the buffer is too short.
+pub fn xyz_at(img: &dyn crate::ImageStride<Coord3D_ABC32f>, x: u32, y: u32) -> Option<[f32; 3]> {
+    if x >= img.width() || y >= img.height() {
+        return None;
+    }
+    let offset = crate::roi_byte_offset(x, y, img.stride(), 12)?;
+    let bytes = img.image_data().get(offset..offset.checked_add(12)?)?;
+    Some(Coord3D_ABC32f::decode(bytes))
+}
+
+#[test]
+fn test_xyz_at() {
+    let mut buf = [0u8; 2 * 28];
+    Coord3D_ABC32f::encode([1.5, -2.0, 1e6], &mut buf[28 + 12..]);
+    let im = crate::image_ref::ImageRef::<Coord3D_ABC32f>::new(2, 2, 28, &buf).unwrap();
+    assert_eq!(crate::min_stride::<Coord3D_ABC32f>(2), 24);
+    assert_eq!(xyz_at(&im, 1, 1), Some([1.5, -2.0, 1e6]));
+    assert_eq!(xyz_at(&im, 0, 0), Some([0.0; 3]));
+    assert_eq!(xyz_at(&im, 2, 0), None);
+    assert_eq!(xyz_at(&im, 0, 2), None);
+    assert_eq!(PixFmt::Coord3D_ABC32f.sample_type(), SampleType::F32);
+    assert_eq!(PixFmt::Coord3D_ABC32f.bits_per_pixel(), 96);
+}
+
+#[test]
+fn test_pixel_codec() {
+    fn roundtrip<F: PixelCodec>(px: F::Pixel) -> F::Pixel {
+        let mut buf = [0u8; 4];
+        F::encode(px, &mut buf);
+        F::decode(&buf)
+    }
+    assert_eq!(roundtrip::<Mono8>(42), 42);
+    assert_eq!(roundtrip::<RGB8>([1, 2, 3]), [1, 2, 3]);
+    assert_eq!(roundtrip::<Mono16>(0x1234), 0x1234);
+
+    let mut buf = [0u8; 2];
+    Mono16::encode(0x1234, &mut buf);
+    assert_eq!(buf, [0x34, 0x12]);
+}
@@ -0,0 +1,89 @@
+//! Typed, zero-copy access to pixels of tightly packed images using [bytemuck]
+
+use crate::{
+    pixel_format::{Mono16, RGB8},
+    ImageStride,
+};
+
+/// Return the pixel bytes of a tightly packed image.
+///
+/// Returns None if the rows are padded or the buffer is too short.
+fn packed_data<F, S: ImageStride<F> + ?Sized>(im: &S, bytes_per_pixel: usize) -> Option<&[u8]> {
+    let row_len = im.width() as usize * bytes_per_pixel;
+    let height = im.height() as usize;
+    if height > 1 && im.stride() != row_len {
+        return None;
+    }
+    im.image_data().get(..row_len * height)
+}
+
+/// A `Mono16` image whose pixels can be accessed as `u16` values.
+pub trait AsPixelsMono16: ImageStride<Mono16> {
+    /// View the pixels as a slice of `u16` values in raster order.
+    ///
+    /// This does not copy the data. Returns None if the image has row padding,
+    /// if the buffer is not aligned for `u16` or if the host is big-endian.
+    fn as_pixels_mono16(&self) -> Option<&[u16]>;
+}
+
+impl<S: ImageStride<Mono16> + ?Sized> AsPixelsMono16 for S {
+    fn as_pixels_mono16(&self) -> Option<&[u16]> {
+        if cfg!(target_endian = "big") {
+            // `Mono16` data is little-endian.
+            return None;
+        }
+        bytemuck::try_cast_slice(packed_data(self, 2)?).ok()
+    }
+}
+
+/// An `RGB8` image whose pixels can be accessed as `[u8; 3]` values.
+pub trait AsPixelsRGB8: ImageStride<RGB8> {
+    /// View the pixels as a slice of `[r, g, b]` values in raster order.
+    ///
+    /// This does not copy the data. Returns None if the image has row padding.
+    fn as_pixels(&self) -> Option<&[[u8; 3]]>;
+}
+
+impl<S: ImageStride<RGB8> + ?Sized> AsPixelsRGB8 for S {
+    fn as_pixels(&self) -> Option<&[[u8; 3]]> {
+        bytemuck::try_cast_slice(packed_data(self, 3)?).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AsPixelsMono16, AsPixelsRGB8};
+    use crate::{
+        owned::OImage,
+        pixel_format::{Mono16, RGB8},
+    };
+
+    #[test]
+    fn test_packed_mono16() {
+        let values: Vec<u16> = vec![1, 2, 0x0102, 0xfffe, 7, 8];
+        let buf: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let im = OImage::<Mono16>::new(3, 2, 6, buf).unwrap();
+        if cfg!(target_endian = "little") {
+            assert_eq!(im.as_pixels_mono16(), Some(&values[..]));
+        }
+    }
+
+    #[test]
+    fn test_padded_mono16() {
+        let im = OImage::<Mono16>::new(3, 2, 8, vec![0u8; 16]).unwrap();
+        assert_eq!(im.as_pixels_mono16(), None);
+    }
+
+    #[test]
+    fn test_rgb8() {
+        let buf = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+        let im = OImage::<RGB8>::new(2, 2, 6, buf).unwrap();
+        assert_eq!(
+            im.as_pixels(),
+            Some(&[[1, 2, 3], [4, 5, 6], [7, 8, 9], [10, 11, 12]][..])
+        );
+
+        let padded = OImage::<RGB8>::new(2, 2, 8, vec![0u8; 14]).unwrap();
+        assert_eq!(padded.as_pixels(), None);
+    }
+}
@@ -0,0 +1,352 @@
+//! Statistics of image data
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    error::DimMismatch,
+    iter::{zip_rows, HasRowChunksExact},
+    ops::same_geometry,
+    pixel_format::{pixfmt, Mono8, SampleType},
+    ImageStride, PixFmt, PixelFormat,
+};
+
+/// Per-channel statistics over the valid pixels of an image.
+///
+/// Each vector has one entry per channel, in the order the channels are
+/// stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageStats {
+    /// The minimum value of each channel.
+    pub min: Vec<u16>,
+    /// The maximum value of each channel.
+    pub max: Vec<u16>,
+    /// The mean value of each channel.
+    pub mean: Vec<f64>,
+}
+
+/// An image for which statistics can be computed.
+pub trait HasStats<F>: ImageStride<F> {
+    /// Compute per-channel minimum, maximum and mean of the valid pixels.
+    ///
+    /// Formats with one 8-bit or 16-bit integer sample per channel for every
+    /// pixel are supported. Returns None for other formats or if the image has
+    /// no pixels.
+    fn stats(&self) -> Option<ImageStats>;
+}
+
+impl<S, F> HasStats<F> for S
+where
+    S: ImageStride<F> + ?Sized,
+    F: PixelFormat,
+{
+    fn stats(&self) -> Option<ImageStats> {
+        let fmt = pixfmt::<F>().ok()?;
+        if fmt.sample_type() == SampleType::F32 {
+            return None;
+        }
+        let (sample_size, decode) = sample_decoder(fmt)?;
+        let channels = fmt.channels() as usize;
+        if self.width() == 0 || self.height() == 0 {
+            return None;
+        }
+
+        let mut min = vec![u16::MAX; channels];
+        let mut max = vec![0u16; channels];
+        let mut sum = vec![0u64; channels];
+        for row in self.rowchunks_exact() {
+            for pixel in row.chunks_exact(sample_size * channels) {
+                for (i, sample) in pixel.chunks_exact(sample_size).enumerate() {
+                    let value = decode(sample) as u16;
+                    min[i] = min[i].min(value);
+                    max[i] = max[i].max(value);
+                    sum[i] += value as u64;
+                }
+            }
+        }
+        let n_pixels = self.width() as f64 * self.height() as f64;
+        let mean = sum.iter().map(|s| *s as f64 / n_pixels).collect();
+        Some(ImageStats { min, max, mean })
+    }
+}
+
+/// The mean of each channel of an image, stored without allocating.
+///
+/// Dereferences to a slice with one entry per channel, in the order the
+/// channels are stored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelMeans {
+    values: [f64; 4],
+    len: usize,
+}
+
+impl std::ops::Deref for ChannelMeans {
+    type Target = [f64];
+    fn deref(&self) -> &[f64] {
+        &self.values[..self.len]
+    }
+}
+
+/// Compute the mean of each channel over the valid pixels.
+///
+/// Formats with up to 4 interleaved channels are supported. Returns None for
+/// other formats, such as those with subsampled or planar chroma, or if the
+/// image has no pixels.
+pub fn channel_means<F: PixelFormat>(src: &dyn ImageStride<F>) -> Option<ChannelMeans> {
+    let fmt = pixfmt::<F>().ok()?;
+    let channels = fmt.channels() as usize;
+    let (sample_size, decode) = sample_decoder(fmt)?;
+    if channels > 4 {
+        return None;
+    }
+    if src.width() == 0 || src.height() == 0 {
+        return None;
+    }
+
+    let mut values = [0.0; 4];
+    for row in src.rowchunks_exact() {
+        for pixel in row.chunks_exact(sample_size * channels) {
+            for (sum, sample) in values.iter_mut().zip(pixel.chunks_exact(sample_size)) {
+                *sum += decode(sample);
+            }
+        }
+    }
+    let n_pixels = src.width() as f64 * src.height() as f64;
+    for sum in values[..channels].iter_mut() {
+        *sum /= n_pixels;
+    }
+    Some(ChannelMeans {
+        values,
+        len: channels,
+    })
+}
+
+/// Decode a single sample from the start of a byte slice.
+type DecodeSample = fn(&[u8]) -> f64;
+
+/// The size in bytes and a decoding function for the samples of a format.
+///
+/// Returns None for formats which do not store one sample per channel for
+/// every pixel, such as those with subsampled or planar chroma.
+fn sample_decoder(fmt: PixFmt) -> Option<(usize, DecodeSample)> {
+    let (sample_size, decode): (usize, DecodeSample) = match fmt.sample_type() {
+        SampleType::U8 => (1, |b| b[0] as f64),
+        SampleType::U16 if fmt == PixFmt::Mono16Be => {
+            (2, |b| u16::from_be_bytes([b[0], b[1]]) as f64)
+        }
+        SampleType::U16 => (2, |b| u16::from_le_bytes([b[0], b[1]]) as f64),
+        SampleType::F32 => (4, |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64),
+    };
+    if fmt.bits_per_pixel() as usize != fmt.channels() as usize * sample_size * 8 {
+        return None;
+    }
+    Some((sample_size, decode))
+}
+
+/// Compute the mean squared error between two images.
+///
+/// The squared differences of all samples of the valid pixels, over every
+/// channel, are averaged. Samples are decoded according to the pixel format,
+/// so e.g. `Mono16` differences are in 16-bit units. Formats without one
+/// sample per channel for every pixel are compared byte by byte. Returns 0 for
+/// images with no pixels and an error if the images have different
+/// dimensions.
+pub fn mse<F: PixelFormat>(
+    a: &dyn ImageStride<F>,
+    b: &dyn ImageStride<F>,
+) -> Result<f64, DimMismatch> {
+    if !same_geometry(a, b) {
+        return Err(DimMismatch);
+    }
+    let (sample_size, decode) = pixfmt::<F>()
+        .ok()
+        .and_then(sample_decoder)
+        .unwrap_or((1, |b| b[0] as f64));
+    let mut sum = 0.0;
+    let mut n_samples = 0u64;
+    for (a_row, b_row) in zip_rows(a, b) {
+        for (x, y) in a_row
+            .chunks_exact(sample_size)
+            .zip(b_row.chunks_exact(sample_size))
+        {
+            let diff = decode(x) - decode(y);
+            sum += diff * diff;
+            n_samples += 1;
+        }
+    }
+    if n_samples == 0 {
+        return Ok(0.0);
+    }
+    Ok(sum / n_samples as f64)
+}
+
+/// Compute the peak signal-to-noise ratio between two images in decibels.
+///
+/// This is `10 * log10(max_value^2 / mse)` where `max_value` is the largest
+/// possible sample value, e.g. 255 for 8-bit formats. Identical images give
+/// infinity. Returns an error if the images have different dimensions.
+#[cfg(feature = "std")]
+pub fn psnr<F: PixelFormat>(
+    a: &dyn ImageStride<F>,
+    b: &dyn ImageStride<F>,
+    max_value: f64,
+) -> Result<f64, DimMismatch> {
+    let mse = mse(a, b)?;
+    if mse == 0.0 {
+        return Ok(f64::INFINITY);
+    }
+    Ok(10.0 * (max_value * max_value / mse).log10())
+}
+
+/// Count the number of pixels with each value.
+///
+/// Entry `i` of the result is the number of valid pixels equal to `i`.
+pub fn histogram_mono8(src: &dyn ImageStride<Mono8>) -> [u64; 256] {
+    let mut hist = [0u64; 256];
+    for row in src.rowchunks_exact() {
+        for value in row {
+            hist[*value as usize] += 1;
+        }
+    }
+    hist
+}
+
+/// Compute the summed-area table of an image.
+///
+/// The table has `(width + 1) * (height + 1)` entries in row-major order, so
+/// entry `y * (width + 1) + x` is the sum of all pixels above and to the left
+/// of `(x, y)`, exclusive. The first row and column are zero. The sum of the
+/// rectangle with corners `(x0, y0)` (inclusive) and `(x1, y1)` (exclusive)
+/// is then `t[y1][x1] - t[y0][x1] - t[y1][x0] + t[y0][x0]`.
+pub fn integral_image_mono8(src: &dyn ImageStride<Mono8>) -> Vec<u64> {
+    let table_width = src.width() as usize + 1;
+    let mut table = vec![0u64; table_width * (src.height() as usize + 1)];
+    for (y, row) in src.rowchunks_exact().enumerate() {
+        let mut row_sum = 0u64;
+        for (x, value) in row.iter().enumerate() {
+            row_sum += *value as u64;
+            let above = table[y * table_width + x + 1];
+            table[(y + 1) * table_width + x + 1] = above + row_sum;
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        owned::OImage,
+        pixel_format::{Mono16, Mono8, RGB8},
+    };
+
+    #[test]
+    fn test_stats_constant() {
+        let im = OImage::<Mono8>::new(3, 2, 4, vec![7; 8]).unwrap();
+        let stats = im.stats().unwrap();
+        assert_eq!(stats.min, vec![7]);
+        assert_eq!(stats.max, vec![7]);
+        assert_eq!(stats.mean, vec![7.0]);
+    }
+
+    #[test]
+    fn test_stats_gradient() {
+        // Padding bytes of 255 are ignored.
+        let im = OImage::<Mono8>::new(4, 2, 5, vec![0, 1, 2, 3, 255, 4, 5, 6, 7]).unwrap();
+        let stats = im.stats().unwrap();
+        assert_eq!(stats.min, vec![0]);
+        assert_eq!(stats.max, vec![7]);
+        assert_eq!(stats.mean, vec![3.5]);
+
+        let buf = vec![0, 100, 200, 10, 110, 210];
+        let im = OImage::<RGB8>::new(2, 1, 6, buf).unwrap();
+        let stats = im.stats().unwrap();
+        assert_eq!(stats.min, vec![0, 100, 200]);
+        assert_eq!(stats.max, vec![10, 110, 210]);
+        assert_eq!(stats.mean, vec![5.0, 105.0, 205.0]);
+
+        let buf = [0u16, 1000, 2000, 3000]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        let im = OImage::<Mono16>::new(4, 1, 8, buf).unwrap();
+        let stats = im.stats().unwrap();
+        assert_eq!(stats.min, vec![0]);
+        assert_eq!(stats.max, vec![3000]);
+        assert_eq!(stats.mean, vec![1500.0]);
+
+        let im = OImage::<crate::pixel_format::NV12>::new(2, 2, 2, vec![0; 6]).unwrap();
+        assert!(im.stats().is_none());
+    }
+
+    #[test]
+    fn test_channel_means() {
+        let im = OImage::<Mono8>::new(3, 2, 4, vec![1, 2, 3, 255, 4, 5, 6]).unwrap();
+        assert_eq!(&channel_means(&im).unwrap()[..], &[3.5]);
+
+        let buf = vec![0, 100, 200, 10, 110, 210];
+        let im = OImage::<RGB8>::new(2, 1, 6, buf).unwrap();
+        assert_eq!(&channel_means(&im).unwrap()[..], &[5.0, 105.0, 205.0]);
+
+        let buf = [0u16, 1000].iter().flat_map(|x| x.to_le_bytes()).collect();
+        let im = OImage::<Mono16>::new(2, 1, 4, buf).unwrap();
+        assert_eq!(&channel_means(&im).unwrap()[..], &[500.0]);
+
+        let im = OImage::<crate::pixel_format::NV12>::new(2, 2, 2, vec![0; 6]).unwrap();
+        assert!(channel_means(&im).is_none());
+    }
+
+    #[test]
+    fn test_mse() {
+        let a = OImage::<Mono8>::new(2, 2, 3, vec![10, 20, 255, 30, 40]).unwrap();
+        assert_eq!(mse(&a, &a), Ok(0.0));
+        assert_eq!(psnr(&a, &a, 255.0), Ok(f64::INFINITY));
+
+        // Padding differs but is ignored.
+        let b = OImage::<Mono8>::new(2, 2, 2, vec![12, 20, 30, 36]).unwrap();
+        assert_eq!(mse(&a, &b), Ok(5.0));
+        let expected = 10.0 * (255.0f64 * 255.0 / 5.0).log10();
+        assert!((psnr(&a, &b, 255.0).unwrap() - expected).abs() < 1e-9);
+
+        let a = OImage::<RGB8>::new(1, 1, 3, vec![0, 0, 0]).unwrap();
+        let b = OImage::<RGB8>::new(1, 1, 3, vec![3, 0, 3]).unwrap();
+        assert_eq!(mse(&a, &b), Ok(6.0));
+
+        let c = OImage::<RGB8>::new(2, 1, 6, vec![0; 6]).unwrap();
+        assert_eq!(mse(&a, &c), Err(DimMismatch));
+    }
+
+    #[test]
+    fn test_histogram() {
+        let im = OImage::<Mono8>::new(3, 2, 4, vec![1, 2, 2, 255, 2, 9, 1, 255]).unwrap();
+        let hist = histogram_mono8(&im);
+        assert_eq!(hist[1], 2);
+        assert_eq!(hist[2], 3);
+        assert_eq!(hist[9], 1);
+        assert_eq!(hist[255], 0);
+        assert_eq!(hist.iter().sum::<u64>(), 6);
+    }
+
+    #[test]
+    fn test_integral_image() {
+        let (width, height) = (5usize, 4usize);
+        let buf: Vec<u8> = (0..(width + 1) * height)
+            .map(|i| (i * 37 % 256) as u8)
+            .collect();
+        let im = OImage::<Mono8>::new(width as u32, height as u32, width + 1, buf.clone()).unwrap();
+        let table = integral_image_mono8(&im);
+        assert_eq!(table.len(), (width + 1) * (height + 1));
+
+        let t = |x: usize, y: usize| table[y * (width + 1) + x];
+        for &(x0, y0, x1, y1) in [(0, 0, 5, 4), (1, 1, 3, 3), (2, 0, 3, 4), (4, 3, 5, 4)].iter() {
+            let mut expected = 0u64;
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    expected += buf[y * (width + 1) + x] as u64;
+                }
+            }
+            let sum = t(x1, y1) + t(x0, y0) - t(x0, y1) - t(x1, y0);
+            assert_eq!(sum, expected, "{:?}", (x0, y0, x1, y1));
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Helpers to upload images as [wgpu](https://wgpu.rs) textures
+
+use wgpu_types::TextureFormat;
+
+use crate::{pixel_format::pixfmt, ImageStride, PixFmt, PixelFormat};
+
+/// The layout of image data in a buffer for a texture copy.
+///
+/// The fields correspond to those of `wgpu::TexelCopyBufferLayout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureCopyLayout {
+    /// The number of bytes between the starts of consecutive rows.
+    ///
+    /// This is None if the stride does not fit in a `u32`.
+    pub bytes_per_row: Option<u32>,
+    /// The number of rows in the image.
+    pub rows_per_image: u32,
+}
+
+/// The texture format best matching a pixel format.
+///
+/// Returns None if there is no matching texture format, e.g. for formats with
+/// three channels, Bayer mosaics and YUV formats.
+pub fn texture_format(fmt: PixFmt) -> Option<TextureFormat> {
+    match fmt {
+        PixFmt::Mono8 => Some(TextureFormat::R8Unorm),
+        PixFmt::Mono16 | PixFmt::Mono16Le => Some(TextureFormat::R16Unorm),
+        PixFmt::Mono32f => Some(TextureFormat::R32Float),
+        PixFmt::RGBA8 => Some(TextureFormat::Rgba8Unorm),
+        _ => None,
+    }
+}
+
+/// An image which can be uploaded as a `wgpu` texture.
+pub trait WgpuTexture<F>: ImageStride<F> {
+    /// The buffer layout of the image for a texture copy.
+    fn texture_copy_layout(&self) -> TextureCopyLayout;
+    /// The texture format best matching the pixel format of the image.
+    ///
+    /// See [texture_format].
+    fn texture_format(&self) -> Option<TextureFormat>;
+}
+
+impl<S, F> WgpuTexture<F> for S
+where
+    S: ImageStride<F> + ?Sized,
+    F: PixelFormat,
+{
+    fn texture_copy_layout(&self) -> TextureCopyLayout {
+        use std::convert::TryFrom;
+        TextureCopyLayout {
+            bytes_per_row: u32::try_from(self.stride()).ok(),
+            rows_per_image: self.height(),
+        }
+    }
+    fn texture_format(&self) -> Option<TextureFormat> {
+        texture_format(pixfmt::<F>().ok()?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        owned::OImage,
+        pixel_format::{RGB8, RGBA8},
+    };
+
+    #[test]
+    fn test_padded_rgba8() {
+        // 3 pixels of 4 bytes with 4 bytes of padding per row.
+        let im = OImage::<RGBA8>::zeros(3, 5, 16).unwrap();
+        assert_eq!(
+            im.texture_copy_layout(),
+            TextureCopyLayout {
+                bytes_per_row: Some(16),
+                rows_per_image: 5,
+            }
+        );
+        assert_eq!(im.texture_format(), Some(TextureFormat::Rgba8Unorm));
+
+        let rgb = OImage::<RGB8>::zeros(3, 5, 9).unwrap();
+        assert_eq!(rgb.texture_format(), None);
+        assert_eq!(texture_format(PixFmt::Mono8), Some(TextureFormat::R8Unorm));
+    }
+}